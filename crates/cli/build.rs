@@ -9,26 +9,34 @@ async fn main() -> Result<()> {
     if let Ok("cargo-clippy") = env::var("CARGO_CFG_FEATURE").as_ref().map(String::as_str) {
         stub_plugin_for_clippy()
     } else {
-        copy_plugin().await
+        copy_plugins().await
     }
 }
 
-// When using clippy, we need to write stubbed plugin.wasm file to ensure
-// compilation succeeds. This skips building the actual plugin.wasm that would
-// be injected into the CLI binary.
+// When using clippy, we need to write stubbed plugin.wasm and
+// plugin_bytecode_only.wasm files to ensure compilation succeeds. This skips
+// building the actual plugins that would be injected into the CLI binary.
 fn stub_plugin_for_clippy() -> Result<()> {
     let out_dir = PathBuf::from(env::var("OUT_DIR")?);
-    let plugin_path = out_dir.join("plugin.wasm");
-
-    if !plugin_path.exists() {
-        std::fs::write(plugin_path, [])?;
-        println!("cargo:warning=using stubbed plugin.wasm for static analysis purposes...");
+    for name in ["plugin.wasm", "plugin_bytecode_only.wasm"] {
+        let plugin_path = out_dir.join(name);
+        if !plugin_path.exists() {
+            std::fs::write(plugin_path, [])?;
+            println!("cargo:warning=using stubbed {name} for static analysis purposes...");
+        }
     }
     Ok(())
 }
 
-// Copy the plugin binary build from the `plugin` crate
-async fn copy_plugin() -> Result<()> {
+// Copy the plugin binaries built from the `plugin` crate
+async fn copy_plugins() -> Result<()> {
+    copy_plugin("plugin").await?;
+    copy_plugin("plugin_bytecode_only").await?;
+    println!("cargo:rerun-if-changed=build.rs");
+    Ok(())
+}
+
+async fn copy_plugin(name: &str) -> Result<()> {
     let cargo_manifest_dir = env::var("CARGO_MANIFEST_DIR")?;
     let module_path = PathBuf::from(&cargo_manifest_dir)
         .parent()
@@ -36,18 +44,24 @@ async fn copy_plugin() -> Result<()> {
         .parent()
         .unwrap()
         .join("target/wasm32-wasip1/release");
-    let plugin_path = module_path.join("plugin.wasm");
-    let plugin_wizened_path = module_path.join("plugin_wizened.wasm");
+    let plugin_path = module_path.join(format!("{name}.wasm"));
+    let plugin_wizened_path = module_path.join(format!("{name}_wizened.wasm"));
 
-    let initialized_plugin =
-        javy_plugin_processing::initialize_plugin(&fs::read(&plugin_path)?).await?;
+    // Function names are kept so modules generated with `-C debug-info`
+    // include them. They're removed from modules generated without it.
+    let initialized_plugin = javy_plugin_processing::initialize_plugin(
+        &fs::read(&plugin_path)?,
+        &javy_plugin_processing::Optimization::default(),
+        true,
+        &[],
+    )
+    .await?;
     fs::write(&plugin_wizened_path, &initialized_plugin)?;
 
     println!("cargo:rerun-if-changed={}", plugin_path.to_str().unwrap());
-    println!("cargo:rerun-if-changed=build.rs");
 
     let out_dir = env::var("OUT_DIR")?;
-    let copied_plugin_path = Path::new(&out_dir).join("plugin.wasm");
+    let copied_plugin_path = Path::new(&out_dir).join(format!("{name}.wasm"));
 
     fs::copy(&plugin_wizened_path, copied_plugin_path)?;
     Ok(())
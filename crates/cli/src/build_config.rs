@@ -0,0 +1,376 @@
+//! Support for reading `javy build` options from a configuration file.
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs, mem,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    commands::{CodegenOption, JsGroupOption},
+    option::{GroupDescriptor, GroupOption, GroupOptionBuilder},
+};
+
+/// The configuration file used when none is specified, if it exists in the
+/// current directory.
+const DEFAULT_BUILD_CONFIG: &str = "javy.toml";
+
+/// The output path used when building a single input and none is specified.
+const DEFAULT_OUTPUT: &str = "index.wasm";
+
+/// One or more paths.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Paths {
+    One(PathBuf),
+    Many(Vec<PathBuf>),
+}
+
+/// The contents of a build configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BuildConfigFile {
+    /// Path of the JavaScript input file, or paths of the input files when
+    /// building multiple modules.
+    input: Option<Paths>,
+    /// Path of the WebAssembly output file, or of the output directory when
+    /// building multiple modules.
+    output: Option<PathBuf>,
+    /// Code generation options, equivalent to `-C`.
+    #[serde(default)]
+    codegen: BTreeMap<String, toml::Value>,
+    /// JavaScript runtime options, equivalent to `-J`.
+    #[serde(default)]
+    javascript: BTreeMap<String, toml::Value>,
+}
+
+/// Build options read from a configuration file.
+///
+/// Relative paths in the file are resolved against the directory containing
+/// the file.
+#[derive(Debug, Default)]
+pub(crate) struct BuildConfig {
+    /// Paths of the JavaScript input files.
+    input: Vec<PathBuf>,
+    /// Path of the WebAssembly output file, or of the output directory when
+    /// building multiple modules.
+    output: Option<PathBuf>,
+    /// Code generation options.
+    codegen: Vec<CodegenOption>,
+    /// JavaScript runtime options.
+    js: Vec<JsGroupOption>,
+}
+
+impl BuildConfig {
+    /// Whether there's a `javy.toml` file in the current directory.
+    pub(crate) fn default_exists() -> bool {
+        Path::new(DEFAULT_BUILD_CONFIG).exists()
+    }
+
+    /// Loads the configuration file at `path` or, if no path is given, the
+    /// `javy.toml` file in the current directory if there is one.
+    pub(crate) fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(path) => path,
+            None if Self::default_exists() => Path::new(DEFAULT_BUILD_CONFIG),
+            None => return Ok(Self::default()),
+        };
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read build configuration {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        Self::parse(&contents, base)
+            .with_context(|| format!("Invalid build configuration {}", path.display()))
+    }
+
+    fn parse(contents: &str, base: &Path) -> Result<Self> {
+        let file: BuildConfigFile = toml::from_str(contents)?;
+
+        let supported_codegen_options = CodegenOption::options();
+        let mut codegen = Vec::with_capacity(file.codegen.len());
+        for (key, value) in file.codegen {
+            if !supported_codegen_options.iter().any(|opt| opt.name == key) {
+                bail!("Unknown codegen option {key}. Use `-C help` for more details");
+            }
+            let option = CodegenOption::parse(&format!("{key}={}", option_value(&key, &value)?))
+                .with_context(|| format!("Invalid value for codegen option {key}"))?;
+            codegen.push(match option {
+                CodegenOption::Wit(path) => CodegenOption::Wit(base.join(path)),
+                CodegenOption::Plugin(path) => CodegenOption::Plugin(base.join(path)),
+                CodegenOption::CacheDir(path) => CodegenOption::CacheDir(base.join(path)),
+                option => option,
+            });
+        }
+
+        // Runtime options are validated against the plugin's config schema
+        // along with the ones given on the command line.
+        let mut js = Vec::with_capacity(file.javascript.len());
+        for (name, value) in file.javascript {
+            let value = option_value(&name, &value)?;
+            js.push(JsGroupOption {
+                name,
+                value: Some(value),
+            });
+        }
+
+        let input = match file.input {
+            None => vec![],
+            Some(Paths::One(path)) => vec![path],
+            Some(Paths::Many(paths)) => paths,
+        };
+
+        Ok(Self {
+            input: input.into_iter().map(|path| base.join(path)).collect(),
+            output: file.output.map(|path| base.join(path)),
+            codegen,
+            js,
+        })
+    }
+
+    /// Returns the pairs of input and output paths to build.
+    ///
+    /// `inputs` and `output` take precedence over the paths in the file. When
+    /// building multiple inputs, the output path is a directory in which each
+    /// module is named after its input.
+    pub(crate) fn targets(
+        &self,
+        inputs: &[PathBuf],
+        output: Option<&Path>,
+    ) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let inputs = if inputs.is_empty() {
+            &self.input
+        } else {
+            inputs
+        };
+        let output = output.or(self.output.as_deref());
+
+        match inputs {
+            [] => bail!("No input file specified"),
+            [input] => Ok(vec![(
+                input.clone(),
+                output.unwrap_or(Path::new(DEFAULT_OUTPUT)).to_path_buf(),
+            )]),
+            inputs => {
+                let dir = output.unwrap_or(Path::new(""));
+                let mut outputs = HashSet::new();
+                let mut targets = Vec::with_capacity(inputs.len());
+                for input in inputs {
+                    let stem = input
+                        .file_stem()
+                        .ok_or_else(|| anyhow!("Invalid input file {}", input.display()))?;
+                    let mut file_name = stem.to_os_string();
+                    file_name.push(".wasm");
+                    let output = dir.join(file_name);
+                    if !outputs.insert(output.clone()) {
+                        bail!("Multiple inputs would be written to {}", output.display());
+                    }
+                    targets.push((input.clone(), output));
+                }
+                Ok(targets)
+            }
+        }
+    }
+
+    /// Returns the codegen options from the file that aren't overridden by
+    /// `overrides`, followed by `overrides`.
+    pub(crate) fn codegen_options(
+        &self,
+        overrides: &[GroupOption<CodegenOption>],
+    ) -> Vec<GroupOption<CodegenOption>> {
+        let overridden = overrides
+            .iter()
+            .flat_map(|group| group.0.iter().map(mem::discriminant))
+            .collect::<Vec<_>>();
+        let mut options = vec![GroupOption(
+            self.codegen
+                .iter()
+                .filter(|option| !overridden.contains(&mem::discriminant(*option)))
+                .cloned()
+                .collect(),
+        )];
+        options.extend(overrides.iter().cloned());
+        options
+    }
+
+    /// Returns the runtime options from the file that aren't overridden by
+    /// `overrides`, followed by `overrides`.
+    pub(crate) fn js_options(&self, overrides: &[JsGroupOption]) -> Vec<JsGroupOption> {
+        self.js
+            .iter()
+            .filter(|option| !overrides.iter().any(|o| o.name == option.name))
+            .chain(overrides)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Converts a TOML value to the representation used on the command line.
+fn option_value(key: &str, value: &toml::Value) -> Result<String> {
+    match value {
+        toml::Value::Boolean(true) => Ok("y".into()),
+        toml::Value::Boolean(false) => Ok("n".into()),
+        toml::Value::Integer(n) => Ok(n.to_string()),
+        toml::Value::String(s) => Ok(s.clone()),
+        _ => Err(anyhow!(
+            "Unsupported value for {key}, expected a boolean, integer or string"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::BuildConfig;
+    use crate::{
+        commands::{CodegenOption, CodegenOptionGroup, JsGroupOption, Source},
+        option::GroupOption,
+    };
+    use anyhow::Result;
+
+    #[test]
+    fn parse_build_config() -> Result<()> {
+        let config = BuildConfig::parse(
+            r#"
+            input = "src/index.js"
+            output = "index.wasm"
+
+            [codegen]
+            source = "omitted"
+            deterministic = true
+            plugin = "plugin.wasm"
+
+            [javascript]
+            text-encoding = false
+            memory-limit = "64MiB"
+            "#,
+            Path::new("project"),
+        )?;
+
+        assert_eq!(
+            vec![(
+                PathBuf::from("project/src/index.js"),
+                PathBuf::from("project/index.wasm")
+            )],
+            config.targets(&[], None)?
+        );
+
+        let group: CodegenOptionGroup = config.codegen_options(&[]).try_into()?;
+        assert_eq!(
+            CodegenOptionGroup {
+                source: Source::Omitted,
+                deterministic: true,
+                plugin: Some(PathBuf::from("project/plugin.wasm")),
+                ..Default::default()
+            },
+            group
+        );
+
+        let js = config.js_options(&[]);
+        assert_eq!(2, js.len());
+        assert!(
+            js.iter()
+                .any(|o| o.name == "text-encoding" && o.value.as_deref() == Some("n"))
+        );
+        assert!(
+            js.iter()
+                .any(|o| o.name == "memory-limit" && o.value.as_deref() == Some("64MiB"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn command_line_overrides_build_config() -> Result<()> {
+        let config = BuildConfig::parse(
+            r#"
+            [codegen]
+            source = "omitted"
+            deterministic = true
+
+            [javascript]
+            text-encoding = false
+            "#,
+            Path::new(""),
+        )?;
+
+        let group: CodegenOptionGroup = config
+            .codegen_options(&[GroupOption(vec![CodegenOption::Source(
+                Source::Uncompressed,
+            )])])
+            .try_into()?;
+        assert_eq!(Source::Uncompressed, group.source);
+        assert!(group.deterministic);
+
+        let js = config.js_options(&[JsGroupOption {
+            name: "text-encoding".to_string(),
+            value: None,
+        }]);
+        assert_eq!(1, js.len());
+        assert_eq!(None, js[0].value);
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_targets() -> Result<()> {
+        let config = BuildConfig::parse(
+            r#"
+            input = ["a.js", "lib/b.js"]
+            output = "out"
+            "#,
+            Path::new(""),
+        )?;
+        assert_eq!(
+            vec![
+                (PathBuf::from("a.js"), PathBuf::from("out/a.wasm")),
+                (PathBuf::from("lib/b.js"), PathBuf::from("out/b.wasm")),
+            ],
+            config.targets(&[], None)?
+        );
+
+        let inputs = [PathBuf::from("c.js"), PathBuf::from("d.js")];
+        assert_eq!(
+            vec![
+                (PathBuf::from("c.js"), PathBuf::from("c.wasm")),
+                (PathBuf::from("d.js"), PathBuf::from("d.wasm")),
+            ],
+            BuildConfig::default().targets(&inputs, None)?
+        );
+        assert_eq!(
+            vec![(PathBuf::from("c.js"), PathBuf::from("index.wasm"))],
+            BuildConfig::default().targets(&inputs[..1], None)?
+        );
+
+        let inputs = [PathBuf::from("a/index.js"), PathBuf::from("b/index.js")];
+        assert_eq!(
+            "Multiple inputs would be written to index.wasm",
+            BuildConfig::default()
+                .targets(&inputs, None)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "No input file specified",
+            BuildConfig::default()
+                .targets(&[], None)
+                .unwrap_err()
+                .to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let err = BuildConfig::parse("inptu = \"index.js\"", Path::new("")).unwrap_err();
+        assert!(err.to_string().contains("unknown field `inptu`"));
+
+        let err = BuildConfig::parse("[codegen]\ndynamc = true", Path::new("")).unwrap_err();
+        assert_eq!(
+            "Unknown codegen option dynamc. Use `-C help` for more details",
+            err.to_string()
+        );
+
+        let err = BuildConfig::parse("[codegen]\nsource = \"foo\"", Path::new("")).unwrap_err();
+        assert_eq!("Invalid value for codegen option source", err.to_string());
+    }
+}
@@ -1,9 +1,8 @@
 use crate::{
-    CliPlugin, Plugin, PluginKind, WitOptions,
+    CliPlugin, WitOptions,
     js_config::{ConfigSchema, JsConfig},
     option::OptionMeta,
     option_group,
-    plugin::PLUGIN_MODULE,
 };
 use anyhow::{Result, anyhow, bail};
 use clap::{
@@ -11,10 +10,8 @@ use clap::{
     builder::{StringValueParser, TypedValueParser, ValueParserFactory},
     error::ErrorKind,
 };
-use std::{
-    collections::{HashMap, HashSet},
-    path::PathBuf,
-};
+use javy_codegen::Optimization;
+use std::{collections::HashMap, path::PathBuf};
 
 use crate::option::{
     GroupDescriptor, GroupOption, GroupOptionBuilder, GroupOptionParser, OptionValue, fmt_help,
@@ -35,7 +32,6 @@ pub struct Cli {
 #[derive(Debug, Subcommand)]
 pub enum Command {
     /// Generates WebAssembly from a JavaScript source.
-    #[command(arg_required_else_help = true)]
     Build(BuildCommandOpts),
     /// Emits the plugin binary that is required to run dynamically
     /// linked WebAssembly modules.
@@ -43,6 +39,10 @@ pub enum Command {
     /// Initializes a plugin binary.
     #[command(arg_required_else_help = true)]
     InitPlugin(InitPluginCommandOpts),
+    /// Prints the information recorded in a WebAssembly module generated by
+    /// Javy.
+    #[command(arg_required_else_help = true)]
+    Inspect(InspectCommandOpts),
     /// Profiling commands for Javy-generated WebAssembly modules.
     #[cfg(feature = "profiler")]
     #[command(arg_required_else_help = true, subcommand)]
@@ -54,13 +54,22 @@ const RUNTIME_CONFIG_ARG_LONG: &str = "javascript";
 
 #[derive(Debug, Parser)]
 pub struct BuildCommandOpts {
-    #[arg(value_name = "INPUT", required = true)]
-    /// Path of the JavaScript input file.
-    pub input: PathBuf,
+    #[arg(value_name = "INPUT")]
+    /// Paths of the JavaScript input files. Required unless specified in the
+    /// build configuration file.
+    pub input: Vec<PathBuf>,
 
-    #[arg(short, default_value = "index.wasm")]
-    /// Desired path of the WebAssembly output file.
-    pub output: PathBuf,
+    #[arg(short)]
+    /// Desired path of the WebAssembly output file (default is index.wasm).
+    /// When building multiple inputs, the directory to write the modules to
+    /// (default is the current directory), each named after its input.
+    pub output: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Path to a TOML build configuration file (default is javy.toml in the
+    /// current directory, if it exists). Options given on the command line
+    /// take precedence over the ones in the file.
+    pub config: Option<PathBuf>,
 
     #[arg(short = 'C', long = "codegen")]
     /// Code generation options.
@@ -73,6 +82,28 @@ pub struct BuildCommandOpts {
     pub js: Vec<JsGroupOption>,
 }
 
+impl BuildCommandOpts {
+    /// Prints the help for the `build` command and exits if no arguments were
+    /// given and there's no build configuration file to read them from.
+    pub fn exit_with_help_if_empty(&self, has_build_config: bool) {
+        let empty = self.input.is_empty()
+            && self.output.is_none()
+            && self.config.is_none()
+            && self.codegen.is_empty()
+            && self.js.is_empty();
+        if !empty || has_build_config {
+            return;
+        }
+        let mut command = Cli::command();
+        command.build();
+        let build = command
+            .find_subcommand_mut("build")
+            .expect("build subcommand to exist");
+        eprint!("{}", build.render_help());
+        std::process::exit(2);
+    }
+}
+
 #[derive(Debug, Parser)]
 pub struct EmitPluginCommandOpts {
     #[structopt(short, long)]
@@ -93,6 +124,29 @@ pub struct InitPluginCommandOpts {
     /// zero-filled RNG during pre-initialization. Security note: both
     /// secure_random and insecure_random become non-secure.
     pub deterministic: bool,
+    #[arg(long, default_value = "speed")]
+    /// wasm-opt optimizations to run on the plugin: none, speed, size,
+    /// aggressive-size, or a comma separated list of wasm-opt passes.
+    pub opt: Optimization,
+    #[arg(long)]
+    /// Keep the plugin's function names so they can be included in modules
+    /// generated with `-C debug-info`.
+    pub debug_info: bool,
+    #[arg(long, value_name = "IMPORTS", value_delimiter = ',')]
+    /// Comma separated list of function imports, as module.name, to transform
+    /// with Binaryen's asyncify so the host can implement them asynchronously.
+    pub asyncify_imports: Vec<String>,
+}
+
+#[derive(Debug, Parser)]
+pub struct InspectCommandOpts {
+    #[arg(value_name = "MODULE", required = true)]
+    /// Path to the module to inspect.
+    pub module: PathBuf,
+    #[arg(long, value_name = "PLUGIN")]
+    /// Path to a plugin to check the dynamically linked module can be run
+    /// with. Fails if the plugin isn't the one the module was generated with.
+    pub check_plugin: Option<PathBuf>,
 }
 
 impl<T> ValueParserFactory for GroupOption<T>
@@ -162,6 +216,12 @@ pub struct CodegenOptionGroup {
     pub source: Source,
     pub plugin: Option<PathBuf>,
     pub deterministic: bool,
+    pub cache_dir: Option<PathBuf>,
+    pub pre_evaluate: bool,
+    pub opt: Optimization,
+    pub strip_unused: bool,
+    pub debug_info: bool,
+    pub compress_bytecode: bool,
 }
 
 impl Default for CodegenOptionGroup {
@@ -172,6 +232,12 @@ impl Default for CodegenOptionGroup {
             source: Source::Compressed,
             plugin: None,
             deterministic: false,
+            cache_dir: None,
+            pre_evaluate: false,
+            opt: Optimization::Speed,
+            strip_unused: false,
+            debug_info: false,
+            compress_bytecode: false,
         }
     }
 }
@@ -195,14 +261,51 @@ option_group! {
         /// time.
         Source(Source),
         /// Optional path to Javy plugin Wasm module. Required for dynamically
-        /// linked modules. JavaScript config options are also not supported when
-        /// using this parameter.
+        /// linked modules. JavaScript config options are only supported when
+        /// using this parameter with plugins exporting `config-schema`.
         Plugin(PathBuf),
         /// Produce deterministic output by using fixed clocks and constant
         /// zero-filled RNG during pre-initialization. Ensures identical input
         /// always produces identical output. Security note: both
         /// secure_random and insecure_random become non-secure.
         Deterministic(bool),
+        /// Optional path to a directory in which to cache the plugin after
+        /// pre-initialization. Subsequent builds using the same plugin,
+        /// JavaScript config options and `deterministic` setting reuse the
+        /// cached plugin instead of pre-initializing it again. Only applies to
+        /// statically linked modules.
+        CacheDir(PathBuf),
+        /// Evaluate the top-level scope of the JavaScript at build time and
+        /// snapshot the result into the generated module, so exported
+        /// functions don't evaluate the module again when called. Output
+        /// written by the top-level scope is written at build time, values
+        /// from `Date.now()` are from build time, and `Math.random()` returns
+        /// the same numbers in every instance if the top-level scope calls it.
+        /// Building fails if the top-level scope uses `Javy.exit` or
+        /// `Javy.exitCode`. Not supported with dynamic linking.
+        PreEvaluate(bool),
+        /// The wasm-opt optimizations to run on statically linked modules.
+        /// Options are `none`, `speed`, `size`, `aggressive-size`, or a comma
+        /// separated list of wasm-opt passes to run in order. `speed`, the
+        /// default, optimizes for performance while `size` and
+        /// `aggressive-size` generate smaller modules at the cost of
+        /// performance.
+        Opt(Optimization),
+        /// Remove engine code the module can't call, such as the code of
+        /// JavaScript intrinsics disabled with `-J`, and report the size
+        /// savings. Intrinsics that aren't needed at runtime, like `eval`,
+        /// should be disabled for the most savings. Not supported with dynamic
+        /// linking.
+        StripUnused(bool),
+        /// Keep the function names of the plugin in a `name` custom section so
+        /// traps and profilers show the names of the functions being executed
+        /// instead of their indices. Generates larger modules.
+        DebugInfo(bool),
+        /// Compress the JavaScript bytecode embedded in the module with
+        /// Brotli, generating smaller modules that take longer to start.
+        /// Requires a plugin that can evaluate compressed bytecode, like the
+        /// default plugin.
+        CompressBytecode(bool),
     }
 }
 
@@ -220,6 +323,12 @@ impl TryFrom<Vec<GroupOption<CodegenOption>>> for CodegenOptionGroup {
         let mut source_specified = false;
         let mut plugin_specified = false;
         let mut deterministic_specified = false;
+        let mut cache_dir_specified = false;
+        let mut pre_evaluate_specified = false;
+        let mut opt_specified = false;
+        let mut strip_unused_specified = false;
+        let mut debug_info_specified = false;
+        let mut compress_bytecode_specified = false;
 
         for option in value.iter().flat_map(|i| i.0.iter()) {
             match option {
@@ -265,6 +374,48 @@ impl TryFrom<Vec<GroupOption<CodegenOption>>> for CodegenOptionGroup {
                     options.deterministic = *enabled;
                     deterministic_specified = true;
                 }
+                CodegenOption::CacheDir(path) => {
+                    if cache_dir_specified {
+                        bail!("cache-dir can only be specified once");
+                    }
+                    options.cache_dir = Some(path.clone());
+                    cache_dir_specified = true;
+                }
+                CodegenOption::PreEvaluate(enabled) => {
+                    if pre_evaluate_specified {
+                        bail!("pre-evaluate can only be specified once");
+                    }
+                    options.pre_evaluate = *enabled;
+                    pre_evaluate_specified = true;
+                }
+                CodegenOption::Opt(opt) => {
+                    if opt_specified {
+                        bail!("opt can only be specified once");
+                    }
+                    options.opt = opt.clone();
+                    opt_specified = true;
+                }
+                CodegenOption::StripUnused(enabled) => {
+                    if strip_unused_specified {
+                        bail!("strip-unused can only be specified once");
+                    }
+                    options.strip_unused = *enabled;
+                    strip_unused_specified = true;
+                }
+                CodegenOption::DebugInfo(enabled) => {
+                    if debug_info_specified {
+                        bail!("debug-info can only be specified once");
+                    }
+                    options.debug_info = *enabled;
+                    debug_info_specified = true;
+                }
+                CodegenOption::CompressBytecode(enabled) => {
+                    if compress_bytecode_specified {
+                        bail!("compress-bytecode can only be specified once");
+                    }
+                    options.compress_bytecode = *enabled;
+                    compress_bytecode_specified = true;
+                }
             }
         }
 
@@ -282,6 +433,14 @@ impl TryFrom<Vec<GroupOption<CodegenOption>>> for CodegenOptionGroup {
             bail!("Must specify plugin when using dynamic linking");
         }
 
+        if options.dynamic && options.pre_evaluate {
+            bail!("Cannot pre-evaluate dynamically linked modules");
+        }
+
+        if options.dynamic && options.strip_unused {
+            bail!("Cannot strip unused code from dynamically linked modules");
+        }
+
         Ok(options)
     }
 }
@@ -290,9 +449,9 @@ impl TryFrom<Vec<GroupOption<CodegenOption>>> for CodegenOptionGroup {
 #[derive(Debug, Clone)]
 pub(super) struct JsGroupOption {
     /// The property name used for the option.
-    name: String,
-    /// Whether the config is enabled or not.
-    enabled: bool,
+    pub(super) name: String,
+    /// The value given for the option, if any.
+    pub(super) value: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -317,73 +476,82 @@ impl TypedValueParser for JsGroupOptionParser {
     ) -> std::result::Result<Self::Value, clap::Error> {
         let val = StringValueParser::new().parse_ref(cmd, arg, value)?;
 
-        if val == "help" {
-            let cli_plugin = CliPlugin::new(
-                Plugin::new(PLUGIN_MODULE.into()).expect("default plugin to be valid"),
-                PluginKind::Default,
-            );
-            let supported_properties = ConfigSchema::from_cli_plugin(&cli_plugin)
-                .expect("config schema to be retrievable")
-                .map_or(Vec::new(), |schema| schema.supported_properties);
-            fmt_help(
-                RUNTIME_CONFIG_ARG_LONG,
-                &RUNTIME_CONFIG_ARG_SHORT.to_string(),
-                &supported_properties
-                    .into_iter()
-                    .map(|prop| OptionMeta {
-                        name: prop.name,
-                        help: "[=y|n]".to_string(),
-                        doc: prop.doc,
-                    })
-                    .collect::<Vec<_>>(),
-            );
-            std::process::exit(0);
-        }
-
         let mut splits = val.splitn(2, '=');
         let key = splits.next().unwrap();
-        let value = match splits.next() {
-            Some("y") => true,
-            Some("n") => false,
-            None => true,
-            _ => return Err(clap::Error::new(clap::error::ErrorKind::InvalidValue)),
-        };
         Ok(JsGroupOption {
             name: key.to_string(),
-            enabled: value,
+            value: splits.next().map(str::to_string),
         })
     }
 }
 
 impl JsConfig {
     /// Build a JS runtime config from valid runtime config values.
+    ///
+    /// The supported properties are retrieved from the plugin's
+    /// `config-schema` export. Prints them and exits if `help` is one of the
+    /// values.
+    ///
+    /// Dynamically linked modules can't disable the properties the plugin
+    /// enables by default since they're applied to a runtime that was already
+    /// initialized with them, so doing so is an error if `dynamic` is `true`.
     pub(super) fn from_group_values(
         cli_plugin: &CliPlugin,
         group_values: Vec<JsGroupOption>,
+        dynamic: bool,
     ) -> Result<JsConfig> {
-        // Always attempt to fetch the supported properties from a plugin.
-        let supported_properties = ConfigSchema::from_cli_plugin(cli_plugin)?
-            .map_or(Vec::new(), |schema| schema.supported_properties);
+        let schema = ConfigSchema::from_plugin(cli_plugin.as_plugin())?;
+        let Some(schema) = schema else {
+            if !group_values.is_empty() {
+                let msg = "JavaScript runtime options (-J) are not supported by the plugin (-C plugin=...) because it doesn't export `config-schema`";
+                Cli::command().error(ErrorKind::InvalidValue, msg).exit();
+            }
+            return Ok(JsConfig::default());
+        };
+        let supported_properties = schema.supported_properties;
 
-        let mut supported_names = HashSet::new();
-        for property in &supported_properties {
-            supported_names.insert(property.name.as_str());
+        if group_values.iter().any(|option| option.name == "help") {
+            fmt_help(
+                RUNTIME_CONFIG_ARG_LONG,
+                &RUNTIME_CONFIG_ARG_SHORT.to_string(),
+                &supported_properties
+                    .iter()
+                    .map(|prop| OptionMeta {
+                        help: prop.help(),
+                        doc: prop.doc_with_default(),
+                        name: prop.name.clone(),
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            std::process::exit(0);
         }
 
         let mut config = HashMap::new();
-        for JsGroupOption { name, enabled } in group_values {
-            if supported_names.contains(name.as_str()) {
+        for JsGroupOption { name, value } in group_values {
+            if let Some(property) = supported_properties
+                .iter()
+                .find(|property| property.name == name)
+            {
                 if config.contains_key(&name) {
                     bail!("{name} can only be specified once");
                 }
-                config.insert(name, enabled);
+                let value = property.parse(value.as_deref())?;
+                if dynamic
+                    && value == serde_json::Value::Bool(false)
+                    && property.default == Some(serde_json::Value::Bool(true))
+                {
+                    bail!(
+                        "{name} can't be disabled in dynamically linked modules because the plugin enables it by default"
+                    );
+                }
+                config.insert(name, value);
             } else {
-                let msg = if matches!(cli_plugin.kind, PluginKind::User) {
-                    "JavaScript runtime options (-J) are not supported when using a plugin (-C plugin=...)".into()
-                } else {
-                    format!("Property {name} is not supported for runtime configuration",)
-                };
-                Cli::command().error(ErrorKind::InvalidValue, msg).exit();
+                Cli::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        format!("Property {name} is not supported for runtime configuration"),
+                    )
+                    .exit();
             }
         }
         Ok(JsConfig::from_hash(config))
@@ -401,14 +569,15 @@ mod tests {
         plugin::PLUGIN_MODULE,
     };
 
-    use super::{CodegenOption, CodegenOptionGroup, GroupOption};
+    use super::{CodegenOption, CodegenOptionGroup, GroupOption, Optimization};
     use anyhow::{Error, Result};
+    use serde_json::json;
 
     #[test]
     fn js_config_from_config_values() -> Result<()> {
         let plugin = CliPlugin::new(Plugin::new(PLUGIN_MODULE.into())?, PluginKind::Default);
 
-        let group = JsConfig::from_group_values(&plugin, vec![])?;
+        let group = JsConfig::from_group_values(&plugin, vec![], false)?;
         assert_eq!(group.get("javy-stream-io"), None);
         assert_eq!(group.get("simd-json-builtins"), None);
         assert_eq!(group.get("text-encoding"), None);
@@ -417,76 +586,148 @@ mod tests {
             &plugin,
             vec![JsGroupOption {
                 name: "javy-stream-io".to_string(),
-                enabled: false,
+                value: Some("n".to_string()),
             }],
+            false,
         )?;
-        assert_eq!(group.get("javy-stream-io"), Some(false));
+        assert_eq!(group.get("javy-stream-io"), Some(&json!(false)));
 
         let group = JsConfig::from_group_values(
             &plugin,
             vec![JsGroupOption {
                 name: "javy-stream-io".to_string(),
-                enabled: true,
+                value: Some("y".to_string()),
             }],
+            false,
         )?;
-        assert_eq!(group.get("javy-stream-io"), Some(true));
+        assert_eq!(group.get("javy-stream-io"), Some(&json!(true)));
 
         let group = JsConfig::from_group_values(
             &plugin,
             vec![JsGroupOption {
                 name: "simd-json-builtins".to_string(),
-                enabled: false,
+                value: Some("n".to_string()),
             }],
+            false,
         )?;
-        assert_eq!(group.get("simd-json-builtins"), Some(false));
+        assert_eq!(group.get("simd-json-builtins"), Some(&json!(false)));
 
         let group = JsConfig::from_group_values(
             &plugin,
             vec![JsGroupOption {
                 name: "simd-json-builtins".to_string(),
-                enabled: true,
+                value: Some("y".to_string()),
             }],
+            false,
         )?;
-        assert_eq!(group.get("simd-json-builtins"), Some(true));
+        assert_eq!(group.get("simd-json-builtins"), Some(&json!(true)));
 
         let group = JsConfig::from_group_values(
             &plugin,
             vec![JsGroupOption {
                 name: "text-encoding".to_string(),
-                enabled: false,
+                value: Some("n".to_string()),
             }],
+            false,
         )?;
-        assert_eq!(group.get("text-encoding"), Some(false));
+        assert_eq!(group.get("text-encoding"), Some(&json!(false)));
 
         let group = JsConfig::from_group_values(
             &plugin,
             vec![JsGroupOption {
                 name: "text-encoding".to_string(),
-                enabled: true,
+                value: Some("y".to_string()),
             }],
+            false,
         )?;
-        assert_eq!(group.get("text-encoding"), Some(true));
+        assert_eq!(group.get("text-encoding"), Some(&json!(true)));
 
         let group = JsConfig::from_group_values(
             &plugin,
             vec![
                 JsGroupOption {
                     name: "javy-stream-io".to_string(),
-                    enabled: false,
+                    value: Some("n".to_string()),
                 },
                 JsGroupOption {
                     name: "simd-json-builtins".to_string(),
-                    enabled: false,
+                    value: Some("n".to_string()),
                 },
                 JsGroupOption {
                     name: "text-encoding".to_string(),
-                    enabled: false,
+                    value: Some("n".to_string()),
                 },
             ],
+            false,
         )?;
-        assert_eq!(group.get("javy-stream-io"), Some(false));
-        assert_eq!(group.get("simd-json-builtins"), Some(false));
-        assert_eq!(group.get("text-encoding"), Some(false));
+        assert_eq!(group.get("javy-stream-io"), Some(&json!(false)));
+        assert_eq!(group.get("simd-json-builtins"), Some(&json!(false)));
+        assert_eq!(group.get("text-encoding"), Some(&json!(false)));
+
+        let group = JsConfig::from_group_values(
+            &plugin,
+            vec![
+                JsGroupOption {
+                    name: "memory-limit".to_string(),
+                    value: Some("64MiB".to_string()),
+                },
+                JsGroupOption {
+                    name: "gc-threshold".to_string(),
+                    value: Some("1024".to_string()),
+                },
+                JsGroupOption {
+                    name: "max-stack-size".to_string(),
+                    value: Some("512KiB".to_string()),
+                },
+            ],
+            false,
+        )?;
+        assert_eq!(group.get("memory-limit"), Some(&json!(64 * 1024 * 1024)));
+        assert_eq!(group.get("gc-threshold"), Some(&json!(1024)));
+        assert_eq!(group.get("max-stack-size"), Some(&json!(512 * 1024)));
+
+        let result = JsConfig::from_group_values(
+            &plugin,
+            vec![JsGroupOption {
+                name: "memory-limit".to_string(),
+                value: None,
+            }],
+            false,
+        );
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "memory-limit requires a value"
+        );
+
+        let group = JsConfig::from_group_values(
+            &plugin,
+            vec![
+                JsGroupOption {
+                    name: "text-encoding".to_string(),
+                    value: Some("y".to_string()),
+                },
+                JsGroupOption {
+                    name: "event-loop".to_string(),
+                    value: Some("n".to_string()),
+                },
+            ],
+            true,
+        )?;
+        assert_eq!(group.get("text-encoding"), Some(&json!(true)));
+        assert_eq!(group.get("event-loop"), Some(&json!(false)));
+
+        let result = JsConfig::from_group_values(
+            &plugin,
+            vec![JsGroupOption {
+                name: "text-encoding".to_string(),
+                value: Some("n".to_string()),
+            }],
+            true,
+        );
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "text-encoding can't be disabled in dynamically linked modules because the plugin enables it by default"
+        );
 
         Ok(())
     }
@@ -536,6 +777,28 @@ mod tests {
             "Must specify plugin when using dynamic linking"
         );
 
+        let raw = vec![GroupOption(vec![
+            CodegenOption::Dynamic(true),
+            CodegenOption::Plugin(PathBuf::from("file.wasm")),
+            CodegenOption::PreEvaluate(true),
+        ])];
+        let result: Result<CodegenOptionGroup, Error> = raw.try_into();
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Cannot pre-evaluate dynamically linked modules"
+        );
+
+        let raw = vec![GroupOption(vec![
+            CodegenOption::Dynamic(true),
+            CodegenOption::Plugin(PathBuf::from("file.wasm")),
+            CodegenOption::StripUnused(true),
+        ])];
+        let result: Result<CodegenOptionGroup, Error> = raw.try_into();
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Cannot strip unused code from dynamically linked modules"
+        );
+
         Ok(())
     }
 
@@ -590,6 +853,26 @@ mod tests {
             result.err().unwrap().to_string(),
             "plugin can only be specified once"
         );
+
+        let raw = vec![GroupOption(vec![
+            CodegenOption::CacheDir(PathBuf::from("cache")),
+            CodegenOption::CacheDir(PathBuf::from("cache2")),
+        ])];
+        let result: Result<CodegenOptionGroup, Error> = raw.try_into();
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "cache-dir can only be specified once"
+        );
+
+        let raw = vec![GroupOption(vec![
+            CodegenOption::Opt(Optimization::Size),
+            CodegenOption::Opt(Optimization::None),
+        ])];
+        let result: Result<CodegenOptionGroup, Error> = raw.try_into();
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "opt can only be specified once"
+        );
         Ok(())
     }
 
@@ -601,13 +884,14 @@ mod tests {
             vec![
                 JsGroupOption {
                     name: "javy-stream-io".to_string(),
-                    enabled: false,
+                    value: Some("n".to_string()),
                 },
                 JsGroupOption {
                     name: "javy-stream-io".to_string(),
-                    enabled: true,
+                    value: Some("y".to_string()),
                 },
             ],
+            false,
         );
         assert_eq!(
             result.err().unwrap().to_string(),
@@ -1,9 +1,10 @@
-use anyhow::{Result, anyhow};
+use anyhow::{Result, anyhow, bail};
 use serde::Deserialize;
+use serde_json::Value;
 use std::{collections::HashMap, str};
 use wasmtime::{AsContext, AsContextMut, Engine, Linker};
 
-use crate::{CliPlugin, PluginKind};
+use crate::Plugin;
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -12,44 +13,46 @@ pub(crate) struct ConfigSchema {
 }
 
 impl ConfigSchema {
-    pub(crate) fn from_cli_plugin(cli_plugin: &CliPlugin) -> Result<Option<ConfigSchema>> {
-        match cli_plugin.kind {
-            PluginKind::User => Ok(None),
-            PluginKind::Default => {
-                let engine = Engine::default();
-                let module = wasmtime::Module::new(&engine, cli_plugin.as_plugin().as_bytes())?;
-                let mut linker = Linker::new(&engine);
-                let mut store = wasmtime::Store::new(&engine, ());
-                linker.define_unknown_imports_as_default_values(&mut store, &module)?;
-                let instance = linker.instantiate(store.as_context_mut(), &module)?;
-
-                let ret_area = instance
-                    .get_typed_func::<(), i32>(store.as_context_mut(), "config-schema")?
-                    .call(store.as_context_mut(), ())?;
-                let memory = instance
-                    .get_memory(store.as_context_mut(), "memory")
-                    .ok_or_else(|| anyhow!("Missing memory export"))?;
-                let mut buf = [0; 8];
-                memory.read(store.as_context(), ret_area as usize, &mut buf)?;
-                let offset = u32::from_le_bytes(buf[0..4].try_into().unwrap());
-                let len = u32::from_le_bytes(buf[4..8].try_into().unwrap());
-                let mut config_json = vec![0; len as usize];
-                memory.read(store.as_context(), offset as usize, &mut config_json)?;
-
-                let config_schema = serde_json::from_slice::<ConfigSchema>(&config_json)?;
-                let mut configs = Vec::with_capacity(config_schema.supported_properties.len());
-                for config in config_schema.supported_properties {
-                    configs.push(JsConfigProperty {
-                        name: config.name,
-                        doc: config.doc,
-                    });
-                }
+    /// Query the schema of a plugin, if it exports `config-schema`.
+    pub(crate) fn from_plugin(plugin: &Plugin) -> Result<Option<ConfigSchema>> {
+        let engine = Engine::default();
+        let module = wasmtime::Module::new(&engine, plugin.as_bytes())?;
+        if module.get_export("config-schema").is_none() {
+            return Ok(None);
+        }
+        let mut linker = Linker::new(&engine);
+        let mut store = wasmtime::Store::new(&engine, ());
+        linker.define_unknown_imports_as_default_values(&mut store, &module)?;
+        let instance = linker.instantiate(store.as_context_mut(), &module)?;
 
-                Ok(Some(Self {
-                    supported_properties: configs,
-                }))
-            }
+        let ret_area = instance
+            .get_typed_func::<(), i32>(store.as_context_mut(), "config-schema")?
+            .call(store.as_context_mut(), ())?;
+        let memory = instance
+            .get_memory(store.as_context_mut(), "memory")
+            .ok_or_else(|| anyhow!("Missing memory export"))?;
+        let mut buf = [0; 8];
+        memory.read(store.as_context(), ret_area as usize, &mut buf)?;
+        let offset = u32::from_le_bytes(buf[0..4].try_into().unwrap());
+        let len = u32::from_le_bytes(buf[4..8].try_into().unwrap());
+        let mut config_json = vec![0; len as usize];
+        memory.read(store.as_context(), offset as usize, &mut config_json)?;
+
+        let config_schema = serde_json::from_slice::<ConfigSchema>(&config_json)?;
+        let mut configs = Vec::with_capacity(config_schema.supported_properties.len());
+        for config in config_schema.supported_properties {
+            configs.push(JsConfigProperty {
+                name: config.name,
+                doc: config.doc,
+                ty: config.ty,
+                default: config.default,
+                allowed_values: config.allowed_values,
+            });
         }
+
+        Ok(Some(Self {
+            supported_properties: configs,
+        }))
     }
 }
 
@@ -61,15 +64,143 @@ pub(crate) struct JsConfigProperty {
     pub(crate) name: String,
     /// The documentation to display for the property.
     pub(crate) doc: String,
+    /// The type of value the property accepts.
+    #[serde(rename = "type", default)]
+    pub(crate) ty: JsConfigType,
+    /// The value used when the property isn't specified, if any.
+    #[serde(default)]
+    pub(crate) default: Option<Value>,
+    /// The only values the property accepts, if it's restricted.
+    #[serde(default)]
+    pub(crate) allowed_values: Option<Vec<Value>>,
+}
+
+impl JsConfigProperty {
+    /// The help text describing the values accepted.
+    pub(crate) fn help(&self) -> String {
+        match &self.allowed_values {
+            Some(values) => format!(
+                "={}",
+                values
+                    .iter()
+                    .map(display_value)
+                    .collect::<Vec<_>>()
+                    .join("|")
+            ),
+            None => self.ty.help().to_string(),
+        }
+    }
+
+    /// The documentation to display for the property, including its default
+    /// value.
+    pub(crate) fn doc_with_default(&self) -> String {
+        match &self.default {
+            Some(default) => format!("{} Default: {}", self.doc, display_value(default)),
+            None => self.doc.clone(),
+        }
+    }
+
+    /// Parse the value given on the command line for the property.
+    pub(crate) fn parse(&self, value: Option<&str>) -> Result<Value> {
+        let value = self.ty.parse(&self.name, value)?;
+        if let Some(allowed_values) = &self.allowed_values
+            && !allowed_values.contains(&value)
+        {
+            bail!(
+                "Invalid value {} for {}, expected one of {}",
+                display_value(&value),
+                self.name,
+                allowed_values
+                    .iter()
+                    .map(display_value)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        Ok(value)
+    }
+}
+
+/// Display a value the way it's given on the command line.
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Bool(true) => "y".into(),
+        Value::Bool(false) => "n".into(),
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// The type of value accepted by a property.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum JsConfigType {
+    /// A boolean, specified as `y` or `n`, or no value for `y`.
+    #[default]
+    Bool,
+    /// A non-negative integer no greater than `u32::MAX`, optionally
+    /// suffixed with `KiB`, `MiB` or `GiB`.
+    Integer,
+    /// An arbitrary string.
+    String,
+}
+
+impl JsConfigType {
+    /// The help text describing the values accepted.
+    pub(crate) fn help(&self) -> &'static str {
+        match self {
+            Self::Bool => "[=y|n]",
+            Self::Integer => "=<n>[KiB|MiB|GiB]",
+            Self::String => "=<string>",
+        }
+    }
+
+    /// Parse the value given on the command line for the property `name`.
+    pub(crate) fn parse(&self, name: &str, value: Option<&str>) -> Result<Value> {
+        match (self, value) {
+            (Self::Bool, None | Some("y")) => Ok(Value::Bool(true)),
+            (Self::Bool, Some("n")) => Ok(Value::Bool(false)),
+            (Self::Bool, Some(value)) => bail!("Invalid value {value} for {name}, expected y or n"),
+            (Self::Integer, Some(value)) => {
+                let integer = parse_integer(value).ok_or_else(|| {
+                    anyhow!("Invalid value {value} for {name}, expected an integer")
+                })?;
+                // Plugins are 32-bit so integer properties can't exceed
+                // `u32::MAX`.
+                if integer > u64::from(u32::MAX) {
+                    bail!(
+                        "Invalid value {value} for {name}, must be at most {}",
+                        u32::MAX
+                    );
+                }
+                Ok(Value::from(integer))
+            }
+            (Self::String, Some(value)) => Ok(Value::String(value.to_string())),
+            (Self::Integer | Self::String, None) => bail!("{name} requires a value"),
+        }
+    }
+}
+
+/// Parse a non-negative integer with an optional binary size suffix.
+fn parse_integer(value: &str) -> Option<u64> {
+    let (digits, multiplier) = [("KiB", 1 << 10), ("MiB", 1 << 20), ("GiB", 1 << 30)]
+        .into_iter()
+        .find_map(|(suffix, multiplier)| {
+            value
+                .strip_suffix(suffix)
+                .map(|digits| (digits, multiplier))
+        })
+        .unwrap_or((value, 1));
+    digits.parse::<u64>().ok()?.checked_mul(multiplier)
 }
 
-/// A collection of property names to whether they are enabled.
+/// A collection of property names to their values.
 #[derive(Clone, Debug, Default)]
-pub(crate) struct JsConfig(HashMap<String, bool>);
+pub(crate) struct JsConfig(HashMap<String, Value>);
 
 impl JsConfig {
     /// Create from a hash.
-    pub(crate) fn from_hash(configs: HashMap<String, bool>) -> Self {
+    pub(crate) fn from_hash(configs: HashMap<String, Value>) -> Self {
         JsConfig(configs)
     }
 
@@ -78,9 +209,119 @@ impl JsConfig {
         Ok(serde_json::to_vec(&self.0)?)
     }
 
-    #[cfg(test)]
     /// Retrieve a value for a property name.
-    pub(crate) fn get(&self, name: &str) -> Option<bool> {
-        self.0.get(name).copied()
+    pub(crate) fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigSchema, JsConfigProperty, JsConfigType};
+    use crate::{Plugin, plugin::PLUGIN_MODULE};
+    use anyhow::Result;
+    use serde_json::json;
+
+    #[test]
+    fn default_plugin_schema_reports_types_and_defaults() -> Result<()> {
+        let plugin = Plugin::new(PLUGIN_MODULE.into())?;
+        let schema = ConfigSchema::from_plugin(&plugin)?.unwrap();
+        let property = |name| {
+            schema
+                .supported_properties
+                .iter()
+                .find(|property| property.name == name)
+                .unwrap()
+        };
+
+        for (name, default) in [
+            ("text-encoding", true),
+            ("event-loop", false),
+            ("date", true),
+            ("eval", true),
+            ("regexp-compiler", true),
+            ("regexp", true),
+            ("json", true),
+            ("proxy", true),
+            ("map-set", true),
+            ("typed-array", true),
+            ("promise", true),
+            ("weak-ref", false),
+            ("performance", false),
+        ] {
+            assert_eq!(JsConfigType::Bool, property(name).ty);
+            assert_eq!(Some(json!(default)), property(name).default);
+            assert_eq!(None, property(name).allowed_values);
+        }
+
+        assert_eq!(JsConfigType::Integer, property("memory-limit").ty);
+        assert_eq!(None, property("memory-limit").default);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_values() -> Result<()> {
+        assert_eq!(json!(true), JsConfigType::Bool.parse("a", None)?);
+        assert_eq!(json!(false), JsConfigType::Bool.parse("a", Some("n"))?);
+        assert_eq!(
+            "Invalid value 1 for a, expected y or n",
+            JsConfigType::Bool
+                .parse("a", Some("1"))
+                .unwrap_err()
+                .to_string()
+        );
+
+        assert_eq!(json!(42), JsConfigType::Integer.parse("a", Some("42"))?);
+        assert_eq!(json!(2048), JsConfigType::Integer.parse("a", Some("2KiB"))?);
+        assert_eq!(
+            json!(64 * 1024 * 1024),
+            JsConfigType::Integer.parse("a", Some("64MiB"))?
+        );
+        assert_eq!(
+            json!(1024 * 1024 * 1024),
+            JsConfigType::Integer.parse("a", Some("1GiB"))?
+        );
+        assert_eq!(
+            json!(u32::MAX),
+            JsConfigType::Integer.parse("a", Some("4294967295"))?
+        );
+        assert_eq!(
+            "Invalid value 4GiB for a, must be at most 4294967295",
+            JsConfigType::Integer
+                .parse("a", Some("4GiB"))
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(JsConfigType::Integer.parse("a", Some("-1")).is_err());
+        assert!(JsConfigType::Integer.parse("a", Some("MiB")).is_err());
+        assert_eq!(
+            "a requires a value",
+            JsConfigType::Integer
+                .parse("a", None)
+                .unwrap_err()
+                .to_string()
+        );
+
+        assert_eq!(json!("y"), JsConfigType::String.parse("a", Some("y"))?);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_allowed_values() -> Result<()> {
+        let property: JsConfigProperty = serde_json::from_value(json!({
+            "name": "mode",
+            "doc": "The mode.",
+            "type": "string",
+            "default": "fast",
+            "allowedValues": ["fast", "small"],
+        }))?;
+        assert_eq!("=fast|small", property.help());
+        assert_eq!("The mode. Default: fast", property.doc_with_default());
+        assert_eq!(json!("small"), property.parse(Some("small"))?);
+        assert_eq!(
+            "Invalid value slow for mode, expected one of fast, small",
+            property.parse(Some("slow")).unwrap_err().to_string()
+        );
+        Ok(())
     }
 }
@@ -1,3 +1,4 @@
+mod build_config;
 mod commands;
 mod js_config;
 mod option;
@@ -5,14 +6,19 @@ mod plugin;
 #[cfg(feature = "profiler")]
 mod profiler;
 
-use crate::commands::{Cli, Command, EmitPluginCommandOpts};
-use anyhow::Result;
+use crate::commands::{Cli, Command, EmitPluginCommandOpts, InspectCommandOpts};
+use anyhow::{Result, bail};
 use clap::Parser;
 
+use build_config::BuildConfig;
 use commands::CodegenOptionGroup;
-use javy_codegen::{Generator, JS, LinkingKind, Plugin, SourceEmbedding, WitOptions};
-use js_config::JsConfig;
-use plugin::{CliPlugin, PLUGIN_MODULE, PluginKind, UninitializedPlugin};
+use javy_codegen::{
+    BytecodeInfo, Generator, JS, JavyConfig, LinkingKind, Plugin, SourceEmbedding, WitOptions,
+};
+use js_config::{ConfigSchema, JsConfig};
+use plugin::{
+    BYTECODE_ONLY_PLUGIN_MODULE, CliPlugin, PLUGIN_MODULE, PluginKind, UninitializedPlugin,
+};
 use std::fs;
 use std::fs::File;
 use std::io::Write;
@@ -24,8 +30,12 @@ async fn main() -> Result<()> {
     match &args.command {
         Command::EmitPlugin(opts) => emit_plugin(opts),
         Command::Build(opts) => {
-            let js = JS::from_file(&opts.input)?;
-            let codegen_opts: CodegenOptionGroup = opts.codegen.clone().try_into()?;
+            // Like `arg_required_else_help`, which can't be used since the
+            // inputs may come from the build configuration file.
+            opts.exit_with_help_if_empty(BuildConfig::default_exists());
+            let build_config = BuildConfig::load(opts.config.as_deref())?;
+            let codegen_opts: CodegenOptionGroup =
+                build_config.codegen_options(&opts.codegen).try_into()?;
 
             // Always assume the default plugin if no plugin is provided.
             let cli_plugin = match &codegen_opts.plugin {
@@ -33,9 +43,30 @@ async fn main() -> Result<()> {
                 None => CliPlugin::new(Plugin::new(PLUGIN_MODULE.into())?, PluginKind::Default),
             };
 
-            let js_opts = JsConfig::from_group_values(&cli_plugin, opts.js.clone())?;
+            // The runtime options are resolved before the inputs since `-J help`
+            // lists the options supported by the plugin without any input.
+            let js_opts = JsConfig::from_group_values(
+                &cli_plugin,
+                build_config.js_options(&opts.js),
+                codegen_opts.dynamic,
+            )?;
+
+            let targets = build_config.targets(&opts.input, opts.output.as_deref())?;
+            let sources = targets
+                .iter()
+                .map(|(input, _)| JS::from_file(input))
+                .collect::<Result<Vec<_>>>()?;
+
+            // Statically linked modules that can't evaluate JS at runtime
+            // embed the default plugin without the JS compiler.
+            let bytecode_only = matches!(cli_plugin.kind, PluginKind::Default)
+                && !codegen_opts.dynamic
+                && js_opts.get("eval") == Some(&serde_json::Value::Bool(false));
 
             let mut generator = Generator::new(cli_plugin.into_plugin());
+            if bytecode_only {
+                generator.embedded_plugin(Plugin::new(BYTECODE_ONLY_PLUGIN_MODULE.into())?);
+            }
 
             // Configure the generator with the provided options.
             generator
@@ -51,6 +82,13 @@ async fn main() -> Result<()> {
 
             set_producer_version(&mut generator);
             generator.deterministic(codegen_opts.deterministic);
+            if let Some(dir) = codegen_opts.cache_dir {
+                generator.cache_dir(dir);
+            }
+            generator.pre_evaluate(codegen_opts.pre_evaluate);
+            generator.optimization(codegen_opts.opt);
+            generator.debug_info(codegen_opts.debug_info);
+            generator.compress_bytecode(codegen_opts.compress_bytecode);
 
             if codegen_opts.dynamic {
                 generator.linking(LinkingKind::Dynamic);
@@ -58,9 +96,27 @@ async fn main() -> Result<()> {
                 generator.linking(LinkingKind::Static);
             };
 
-            let wasm = generator.generate(&js).await?;
-
-            fs::write(&opts.output, wasm)?;
+            let modules = generator.generate_all(&sources).await?;
+
+            for ((_, output), wasm) in targets.iter().zip(modules) {
+                let wasm = if codegen_opts.strip_unused {
+                    let stripped = javy_codegen::strip_unused(&wasm)?;
+                    let saved = wasm.len().saturating_sub(stripped.wasm.len());
+                    eprintln!(
+                        "{}: removed {} unused functions, saving {saved} bytes ({:.1}%)",
+                        output.display(),
+                        stripped.removed_functions,
+                        saved as f64 * 100.0 / wasm.len() as f64,
+                    );
+                    stripped.wasm
+                } else {
+                    wasm
+                };
+                if let Some(dir) = output.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                fs::write(output, wasm)?;
+            }
             Ok(())
         }
         #[cfg(feature = "profiler")]
@@ -69,9 +125,13 @@ async fn main() -> Result<()> {
             let plugin_bytes = fs::read(&opts.plugin)?;
             let uninitialized_plugin = UninitializedPlugin::new(&plugin_bytes)?;
             let initialized_plugin_bytes = if opts.deterministic {
-                uninitialized_plugin.initialize_with_determinism().await?
+                uninitialized_plugin
+                    .initialize_with_determinism(&opts.opt, opts.debug_info, &opts.asyncify_imports)
+                    .await?
             } else {
-                uninitialized_plugin.initialize().await?
+                uninitialized_plugin
+                    .initialize(&opts.opt, opts.debug_info, &opts.asyncify_imports)
+                    .await?
             };
 
             let mut out: Box<dyn Write> = match opts.out.as_ref() {
@@ -81,7 +141,57 @@ async fn main() -> Result<()> {
             out.write_all(&initialized_plugin_bytes)?;
             Ok(())
         }
+        Command::Inspect(opts) => inspect(opts),
+    }
+}
+
+fn inspect(opts: &InspectCommandOpts) -> Result<()> {
+    let wasm = fs::read(&opts.module)?;
+    let config = JavyConfig::from_module(&wasm)?;
+    match &config {
+        Some(config) => {
+            println!("Javy version: {}", config.javy_version);
+            println!("Import namespace: {}", config.import_namespace);
+            println!("JavaScript runtime config:");
+            for (name, value) in &config.runtime_config {
+                println!("  {name}: {value}");
+            }
+        }
+        None => println!("Javy config: not recorded"),
     }
+    let bytecode_info = BytecodeInfo::from_module(&wasm)?;
+    match &bytecode_info {
+        Some(info) => {
+            println!("Bytecode version: {}", info.version);
+            println!("Plugin fingerprint: {}", info.plugin_fingerprint);
+        }
+        None => println!("Bytecode version: not recorded"),
+    }
+
+    if let Some(path) = &opts.check_plugin {
+        let Some(info) = bytecode_info else {
+            bail!("Module doesn't record the plugin it was generated with");
+        };
+        let plugin = Plugin::new_from_path(path)?;
+        info.check_plugin(&plugin)?;
+        if let Some(config) = config {
+            config.check_plugin(&plugin)?;
+            // Plugins without a schema can't report the options they support.
+            if let Some(schema) = ConfigSchema::from_plugin(&plugin)? {
+                for name in config.runtime_config.keys() {
+                    if !schema
+                        .supported_properties
+                        .iter()
+                        .any(|property| &property.name == name)
+                    {
+                        bail!("Plugin doesn't support the JavaScript runtime option {name}");
+                    }
+                }
+            }
+        }
+        println!("Plugin is compatible");
+    }
+    Ok(())
 }
 
 fn emit_plugin(opts: &EmitPluginCommandOpts) -> Result<()> {
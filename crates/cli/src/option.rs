@@ -1,4 +1,5 @@
 use anyhow::{Result, bail};
+use javy_codegen::Optimization;
 use std::path::PathBuf;
 
 use crate::commands;
@@ -194,3 +195,19 @@ impl OptionValue for commands::Source {
         }
     }
 }
+
+impl OptionValue for Optimization {
+    fn help() -> &'static str {
+        "=none|speed|size|aggressive-size|pass,..."
+    }
+
+    fn parse(val: Option<&str>) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        match val {
+            Some(v) => v.parse(),
+            None => bail!("Expected optimization argument"),
+        }
+    }
+}
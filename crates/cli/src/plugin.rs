@@ -1,8 +1,13 @@
 use anyhow::{Result, bail};
-use javy_codegen::Plugin;
+use javy_codegen::{Optimization, Plugin};
 
 pub const PLUGIN_MODULE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/plugin.wasm"));
 
+/// The default plugin built without the JS compiler, embedded in statically
+/// linked modules when `eval` is disabled.
+pub const BYTECODE_ONLY_PLUGIN_MODULE: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/plugin_bytecode_only.wasm"));
+
 /// Represents the kind of a plugin.
 // This is an internal detail of this module.
 #[derive(Debug)]
@@ -49,13 +54,35 @@ impl<'a> UninitializedPlugin<'a> {
 
     /// Initializes the plugin with deterministic clocks, RNG, and
     /// single-threaded compilation so identical input produces identical output.
-    pub(crate) async fn initialize_with_determinism(&self) -> Result<Vec<u8>> {
-        javy_plugin_processing::initialize_plugin_with_determinism(self.bytes).await
+    pub(crate) async fn initialize_with_determinism(
+        &self,
+        optimization: &Optimization,
+        debug_info: bool,
+        asyncify_imports: &[String],
+    ) -> Result<Vec<u8>> {
+        javy_plugin_processing::initialize_plugin_with_determinism(
+            self.bytes,
+            optimization,
+            debug_info,
+            asyncify_imports,
+        )
+        .await
     }
 
     /// Initializes the plugin with default (non-deterministic) configuration.
-    pub(crate) async fn initialize(&self) -> Result<Vec<u8>> {
-        javy_plugin_processing::initialize_plugin(self.bytes).await
+    pub(crate) async fn initialize(
+        &self,
+        optimization: &Optimization,
+        debug_info: bool,
+        asyncify_imports: &[String],
+    ) -> Result<Vec<u8>> {
+        javy_plugin_processing::initialize_plugin(
+            self.bytes,
+            optimization,
+            debug_info,
+            asyncify_imports,
+        )
+        .await
     }
 
     fn validate(plugin_bytes: &'a [u8]) -> Result<()> {
@@ -119,10 +146,14 @@ mod tests {
         let plugin_bytes = super::PLUGIN_MODULE;
 
         let plugin = UninitializedPlugin::new(plugin_bytes)?;
-        let first = plugin.initialize_with_determinism().await?;
+        let first = plugin
+            .initialize_with_determinism(&Optimization::default(), false, &[])
+            .await?;
 
         let plugin = UninitializedPlugin::new(plugin_bytes)?;
-        let second = plugin.initialize_with_determinism().await?;
+        let second = plugin
+            .initialize_with_determinism(&Optimization::default(), false, &[])
+            .await?;
 
         assert_eq!(
             first, second,
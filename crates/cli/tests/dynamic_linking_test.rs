@@ -1,6 +1,7 @@
 use anyhow::Result;
 use javy_runner::{Builder, Plugin};
 use javy_test_macros::javy_cli_test;
+use std::{fs, path::Path, process::Command, str};
 
 #[javy_cli_test(dyn = true, root = "tests/dynamic-linking-scripts")]
 pub fn test_dynamic_linking(builder: &mut Builder) -> Result<()> {
@@ -25,6 +26,21 @@ pub fn test_dynamic_linking_with_func(builder: &mut Builder) -> Result<()> {
     Ok(())
 }
 
+#[javy_cli_test(dyn = true, root = "tests/dynamic-linking-scripts")]
+pub fn test_dynamic_linking_with_compressed_bytecode(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder
+        .input("linking-with-func.js")
+        .wit("linking-with-func.wit")
+        .world("foo-test")
+        .compress_bytecode(true)
+        .build()?;
+
+    let (_, logs, _) = runner.exec_func("foo-bar", vec![])?;
+
+    assert_eq!("Toplevel\nIn foo\n", String::from_utf8(logs)?);
+    Ok(())
+}
+
 #[javy_cli_test(dyn = true, root = "tests/dynamic-linking-scripts")]
 pub fn test_dynamic_linking_with_func_without_flag(builder: &mut Builder) -> Result<()> {
     let mut runner = builder.input("linking-with-func-without-flag.js").build()?;
@@ -71,6 +87,38 @@ pub fn test_dynamic_linking_with_arrow_fn(builder: &mut Builder) -> Result<()> {
     Ok(())
 }
 
+#[javy_cli_test(dyn = true, root = "tests/dynamic-linking-scripts")]
+fn test_inspect_check_plugin(builder: &mut Builder) -> Result<()> {
+    let runner = builder.input("console.js").build()?;
+    let dir = tempfile::tempdir()?;
+    let module = dir.path().join("console.wasm");
+    fs::write(&module, &runner.wasm)?;
+    let inspect = |plugin: &Path| {
+        Command::new(env!("CARGO_BIN_EXE_javy"))
+            .arg("inspect")
+            .arg(&module)
+            .arg("--check-plugin")
+            .arg(plugin)
+            .output()
+    };
+
+    let output = inspect(&Plugin::DefaultAsUser.path())?;
+    assert!(
+        output.status.success(),
+        "inspect failed: {}",
+        str::from_utf8(&output.stderr)?
+    );
+    let stdout = str::from_utf8(&output.stdout)?;
+    assert!(stdout.contains("Plugin is compatible"), "{stdout}");
+
+    let output = inspect(&Plugin::UserWasiP1.path())?;
+    assert!(!output.status.success());
+    assert!(
+        str::from_utf8(&output.stderr)?.contains("Module was generated with a different plugin")
+    );
+    Ok(())
+}
+
 #[javy_cli_test(dyn = true, root = "tests/dynamic-linking-scripts")]
 fn test_producers_section_present(builder: &mut Builder) -> Result<()> {
     let runner = builder.input("console.js").build()?;
@@ -78,11 +126,41 @@ fn test_producers_section_present(builder: &mut Builder) -> Result<()> {
 }
 
 #[javy_cli_test(dyn = true, root = "tests/dynamic-linking-scripts")]
-fn test_using_runtime_flag_with_dynamic_triggers_error(builder: &mut Builder) -> Result<()> {
+fn test_dynamic_linking_with_runtime_config(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder.input("promise.js").event_loop(true).build()?;
+
+    let (output, _, _) = runner.exec(vec![])?;
+    assert_eq!("\"foo\"\"bar\"", String::from_utf8(output)?);
+    Ok(())
+}
+
+#[javy_cli_test(dyn = true, root = "tests/dynamic-linking-scripts")]
+fn test_dynamic_linking_with_runtime_config_disabling_enabled_api(
+    builder: &mut Builder,
+) -> Result<()> {
     let build_result = builder.input("console.js").text_encoding(false).build();
+    assert!(build_result.is_err_and(|e| {
+        e.to_string().contains(
+            "text-encoding can't be disabled in dynamically linked modules because the plugin enables it by default",
+        )
+    }));
+    Ok(())
+}
+
+#[javy_cli_test(dyn = true)]
+fn test_using_runtime_flag_with_plugin_without_config_schema_triggers_error(
+    builder: &mut Builder,
+) -> Result<()> {
+    let plugin = Plugin::UserWasiP1;
+    let build_result = builder
+        .plugin(plugin)
+        .preload(plugin.namespace().into(), plugin.path())
+        .input("plugin.js")
+        .text_encoding(false)
+        .build();
     assert!(build_result.is_err_and(|e| {
         e.to_string()
-            .contains("error: JavaScript runtime options (-J) are not supported when using a plugin (-C plugin=...)")
+            .contains("error: JavaScript runtime options (-J) are not supported by the plugin (-C plugin=...) because it doesn't export `config-schema`")
     }));
     Ok(())
 }
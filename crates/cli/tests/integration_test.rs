@@ -1,8 +1,8 @@
 use anyhow::{Result, bail};
 use javy_runner::{Builder, Plugin, Runner, RunnerError, Source};
 use std::{io::Read, path::PathBuf, process::Command, str};
-use wasmtime::{AsContext, AsContextMut, Engine, Linker, Module, Store};
-use wasmtime_wasi::WasiCtxBuilder;
+use wasmtime::{AsContext, AsContextMut, Caller, Engine, Func, Linker, Module, Store};
+use wasmtime_wasi::{I32Exit, WasiCtxBuilder, p1::WasiP1Ctx, p2::pipe::MemoryOutputPipe};
 
 use javy_test_macros::javy_cli_test;
 
@@ -98,6 +98,19 @@ fn test_using_wasip1_plugin_with_static_build(builder: &mut Builder) -> Result<(
     Ok(())
 }
 
+#[javy_cli_test]
+fn test_using_wasip1_plugin_with_host_fns(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder
+        .plugin(Plugin::UserWasiP1)
+        .input("plugin-host-fns.js")
+        .build()?;
+
+    let result = runner.exec(vec![]);
+    assert!(result.is_ok());
+
+    Ok(())
+}
+
 #[javy_cli_test]
 fn test_using_wasip1_plugin_with_export_with_static_build(builder: &mut Builder) -> Result<()> {
     let mut runner = builder
@@ -151,7 +164,7 @@ fn test_using_wasip1_plugin_with_static_build_fails_with_runtime_config(
         .build();
     let err = result.err().unwrap();
     assert!(err.to_string().contains(
-        "JavaScript runtime options (-J) are not supported when using a plugin (-C plugin=...)"
+        "JavaScript runtime options (-J) are not supported by the plugin (-C plugin=...) because it doesn't export `config-schema`"
     ));
 
     Ok(())
@@ -167,12 +180,25 @@ fn test_using_wasip2_plugin_with_static_build_fails_with_runtime_config(
         .build();
     let err = result.err().unwrap();
     assert!(err.to_string().contains(
-        "JavaScript runtime options (-J) are not supported when using a plugin (-C plugin=...)"
+        "JavaScript runtime options (-J) are not supported by the plugin (-C plugin=...) because it doesn't export `config-schema`"
     ));
 
     Ok(())
 }
 
+#[javy_cli_test]
+fn test_using_plugin_with_config_schema_with_runtime_config(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder
+        .plugin(Plugin::DefaultAsUser)
+        .input("promise.js")
+        .event_loop(true)
+        .build()?;
+
+    let (output, _, _) = run(&mut runner, vec![]);
+    assert_eq!("\"foo\"\"bar\"".as_bytes(), output);
+    Ok(())
+}
+
 #[javy_cli_test]
 fn test_using_invalid_plugin_with_static_build_fails(builder: &mut Builder) -> Result<()> {
     let result = builder.plugin(Plugin::InvalidUser).build();
@@ -217,6 +243,15 @@ fn test_promises_without_event_loop(builder: &mut Builder) -> Result<()> {
     Ok(())
 }
 
+#[javy_cli_test(root = "tests/build-config-scripts")]
+fn test_runtime_options_from_build_config(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder.input("promise.js").build()?;
+
+    let (output, _, _) = run(&mut runner, vec![]);
+    assert_eq!("\"foo\"\"bar\"".as_bytes(), output);
+    Ok(())
+}
+
 #[javy_cli_test]
 fn test_promise_top_level_await(builder: &mut Builder) -> Result<()> {
     let mut runner = builder
@@ -244,6 +279,112 @@ fn test_exported_functions(builder: &mut Builder) -> Result<()> {
     Ok(())
 }
 
+#[javy_cli_test]
+fn test_exported_functions_called_on_same_instance(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder
+        .input("exported-fn.js")
+        .wit("exported-fn.wit")
+        .world("exported-fn")
+        .build()?;
+    // The top-level scope is only evaluated by the first call.
+    let (_, logs, _) = runner.exec_funcs(&["foo", "foo-bar", "foo"], vec![])?;
+    assert_eq!(
+        "Hello from top-level\nHello from foo\nHello from fooBar\nHello from foo\n",
+        String::from_utf8(logs)?
+    );
+    Ok(())
+}
+
+#[javy_cli_test]
+fn test_pre_evaluated_exported_functions(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder
+        .input("exported-fn.js")
+        .wit("exported-fn.wit")
+        .world("exported-fn")
+        .pre_evaluate(true)
+        .build()?;
+    // The top-level scope was evaluated at build time.
+    let (_, logs, _) = run_fn(&mut runner, "foo", vec![]);
+    assert_eq!("Hello from foo\n", logs);
+    let (_, logs, _) = run_fn(&mut runner, "foo-bar", vec![]);
+    assert_eq!("Hello from fooBar\n", logs);
+    Ok(())
+}
+
+#[javy_cli_test]
+fn test_pre_evaluated_top_level_exit(builder: &mut Builder) -> Result<()> {
+    let result = builder.input("exit.js").pre_evaluate(true).build();
+
+    let err = result.err().unwrap().to_string();
+    assert!(err.contains("The top-level scope exited with code 3 while being pre-evaluated"));
+    Ok(())
+}
+
+#[javy_cli_test]
+fn test_strip_unused(builder: &mut Builder) -> Result<()> {
+    builder.input("fib.js").eval(false).proxy(false);
+    let unstripped = builder.clone().build()?;
+    let mut runner = builder.strip_unused(true).build()?;
+
+    // The compiler and the `Proxy` implementation can't be called.
+    let function_count = |wasm: &[u8]| -> Result<usize> {
+        Ok(walrus::Module::from_buffer(wasm)?.funcs.iter().count())
+    };
+    assert!(function_count(&runner.wasm)? < function_count(&unstripped.wasm)?);
+    assert!(runner.wasm.len() < unstripped.wasm.len());
+    // The savings are reported as `<output>: removed <n> unused functions,
+    // saving <n> bytes (<n>%)`.
+    let report: Vec<&str> = runner.build_stderr.split_whitespace().collect();
+    let number_after = |word| -> Result<usize> {
+        let index = report.iter().position(|w| *w == word).unwrap();
+        Ok(report[index + 1].parse()?)
+    };
+    assert!(number_after("removed")? > 0);
+    assert!(number_after("saving")? > 0);
+    let (output, _, _) = run_with_u8s(&mut runner, 5);
+    assert_eq!(8, output);
+    Ok(())
+}
+
+#[javy_cli_test]
+fn test_debug_info(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder.input("fib.js").debug_info(true).build()?;
+
+    let module = walrus::Module::from_buffer(&runner.wasm)?;
+    assert!(module.funcs.iter().any(|func| func.name.is_some()));
+    let (output, _, _) = run_with_u8s(&mut runner, 5);
+    assert_eq!(8, output);
+    Ok(())
+}
+
+#[javy_cli_test]
+fn test_compress_bytecode(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder.input("fib.js").compress_bytecode(true).build()?;
+
+    let (output, _, _) = run_with_u8s(&mut runner, 5);
+    assert_eq!(8, output);
+    Ok(())
+}
+
+#[javy_cli_test]
+fn test_eval_disabled(builder: &mut Builder) -> Result<()> {
+    // The bytecode-only plugin is embedded when `eval` is disabled.
+    let mut runner = builder.input("eval.js").eval(false).build()?;
+
+    let (_, logs, _) = run(&mut runner, vec![]);
+    assert_eq!("eval supported: false\n", logs);
+    Ok(())
+}
+
+#[javy_cli_test]
+fn test_eval_enabled(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder.input("eval.js").build()?;
+
+    let (_, logs, _) = run(&mut runner, vec![]);
+    assert_eq!("eval supported: true\n", logs);
+    Ok(())
+}
+
 #[javy_cli_test]
 fn test_exported_promises(builder: &mut Builder) -> Result<()> {
     let mut runner = builder
@@ -288,6 +429,42 @@ fn test_error_handling(builder: &mut Builder) -> Result<()> {
     Ok(())
 }
 
+#[javy_cli_test]
+fn test_exit(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder.input("exit.js").build()?;
+    let result = runner.exec(vec![]);
+    let err = result.err().unwrap().downcast::<RunnerError>().unwrap();
+
+    assert_eq!(3, err.err.downcast_ref::<I32Exit>().unwrap().0);
+    assert_eq!(b"before exit\n".to_vec(), err.stdout);
+    assert_eq!("", err.stderr);
+    Ok(())
+}
+
+#[javy_cli_test]
+fn test_exit_code(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder.input("exit-code.js").build()?;
+    let result = runner.exec(vec![]);
+    let err = result.err().unwrap().downcast::<RunnerError>().unwrap();
+
+    assert_eq!(4, err.err.downcast_ref::<I32Exit>().unwrap().0);
+    assert_eq!(b"hello\n".to_vec(), err.stdout);
+    Ok(())
+}
+
+#[javy_cli_test]
+fn test_memory_limit(builder: &mut Builder) -> Result<()> {
+    let mut runner = builder
+        .input("memory-limit.js")
+        .memory_limit("8MiB")
+        .build()?;
+    let result = runner.exec(vec![]);
+    let err = result.err().unwrap().downcast::<RunnerError>().unwrap();
+
+    assert!(err.stderr.contains("out of memory"));
+    Ok(())
+}
+
 #[javy_cli_test]
 fn test_same_module_outputs_different_random_result(builder: &mut Builder) -> Result<()> {
     let mut runner = builder.input("random.js").build()?;
@@ -412,6 +589,35 @@ fn test_source_code_omitted(builder: &mut Builder) -> Result<()> {
     Ok(())
 }
 
+#[javy_cli_test]
+fn test_inspect(builder: &mut Builder) -> Result<()> {
+    let runner = builder.input("eval.js").eval(false).build()?;
+    let dir = tempfile::tempdir()?;
+    let module = dir.path().join("eval.wasm");
+    std::fs::write(&module, &runner.wasm)?;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_javy"))
+        .arg("inspect")
+        .arg(&module)
+        .output()?;
+    assert!(
+        output.status.success(),
+        "inspect failed: {}",
+        str::from_utf8(&output.stderr)?
+    );
+    let stdout = str::from_utf8(&output.stdout)?;
+    assert!(
+        stdout.contains(&format!("Javy version: {}", env!("CARGO_PKG_VERSION"))),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("Import namespace: javy-default-plugin-v4"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("  eval: false"), "{stdout}");
+    Ok(())
+}
+
 #[test]
 fn test_js_help() -> Result<()> {
     let output = Command::new(env!("CARGO_BIN_EXE_javy"))
@@ -430,6 +636,48 @@ fn test_js_help() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_js_help_with_plugin() -> Result<()> {
+    let plugin = format!("plugin={}", Plugin::DefaultAsUser.path().display());
+    let output = Command::new(env!("CARGO_BIN_EXE_javy"))
+        .args(["build", "-C", &plugin, "-J", "help"])
+        .output()?;
+    assert!(
+        output.status.success(),
+        "build -J help failed: {}",
+        str::from_utf8(&output.stderr)?
+    );
+    let stdout = str::from_utf8(&output.stdout)?;
+    assert!(
+        stdout.contains("event-loop"),
+        "unexpected help output: {stdout}"
+    );
+
+    let plugin = format!("plugin={}", Plugin::UserWasiP1.path().display());
+    let output = Command::new(env!("CARGO_BIN_EXE_javy"))
+        .args(["build", "-C", &plugin, "-J", "help"])
+        .output()?;
+    assert!(!output.status.success());
+    assert!(str::from_utf8(&output.stderr)?.contains("doesn't export `config-schema`"));
+    Ok(())
+}
+
+#[test]
+fn test_build_without_arguments_prints_help() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let output = Command::new(env!("CARGO_BIN_EXE_javy"))
+        .arg("build")
+        .current_dir(dir.path())
+        .output()?;
+    assert_eq!(Some(2), output.status.code());
+    let stderr = str::from_utf8(&output.stderr)?;
+    assert!(
+        stderr.contains("Usage: javy build"),
+        "unexpected help output: {stderr}"
+    );
+    Ok(())
+}
+
 #[test]
 fn test_codegen_help() -> Result<()> {
     let output = Command::new(env!("CARGO_BIN_EXE_javy"))
@@ -557,6 +805,215 @@ fn test_init_plugin_deterministic() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_init_plugin_with_asyncify_imports() -> Result<()> {
+    let uninitialized_plugin = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join("..")
+        .join(
+            std::path::Path::new("target")
+                .join("wasm32-wasip1")
+                .join("release")
+                .join("plugin.wasm"),
+        );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_javy"))
+        .arg("init-plugin")
+        .arg("--asyncify-imports")
+        .arg("wasi_snapshot_preview1.fd_read")
+        .arg(uninitialized_plugin.to_str().unwrap())
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "init-plugin --asyncify-imports failed: {}",
+            str::from_utf8(&output.stderr)?,
+        );
+    }
+
+    // Check the asyncify exports were added and the plugin is initialized.
+    let engine = Engine::default();
+    let mut linker = Linker::new(&engine);
+    wasmtime_wasi::p1::add_to_linker_sync(&mut linker, |s| s)?;
+    let wasi = WasiCtxBuilder::new().build_p1();
+    let mut store = Store::new(&engine, wasi);
+    let module = Module::new(&engine, &output.stdout)?;
+    assert!(module.get_export("asyncify_start_unwind").is_some());
+    let instance = linker.instantiate(store.as_context_mut(), &module)?;
+    instance
+        .get_typed_func::<(i32, i32), i32>(store.as_context_mut(), "compile-src")?
+        .call(store.as_context_mut(), (0, 0))?;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_javy"))
+        .arg("init-plugin")
+        .arg("--asyncify-imports")
+        .arg("some_host.missing")
+        .arg(uninitialized_plugin.to_str().unwrap())
+        .output()?;
+    assert!(!output.status.success());
+    assert!(
+        str::from_utf8(&output.stderr)?
+            .contains("Plugin doesn't import a function named `some_host.missing` to asyncify")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_module_awaiting_asyncify_suspended_host_call() -> Result<()> {
+    let uninitialized_plugin = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join("..")
+        .join(
+            std::path::Path::new("target")
+                .join("wasm32-wasip1")
+                .join("release")
+                .join("test_plugin_uninitialized.wasm"),
+        );
+    let dir = tempfile::tempdir()?;
+    let plugin_path = dir.path().join("plugin.wasm");
+    let js_path = dir.path().join("index.js");
+    let module_path = dir.path().join("index.wasm");
+    std::fs::write(
+        &js_path,
+        "const value = await getValue(); console.log(value);",
+    )?;
+
+    let javy = |args: &[&str]| -> Result<()> {
+        let output = Command::new(env!("CARGO_BIN_EXE_javy"))
+            .args(args)
+            .output()?;
+        if !output.status.success() {
+            bail!(
+                "javy {} failed: {}",
+                args.join(" "),
+                str::from_utf8(&output.stderr)?
+            );
+        }
+        Ok(())
+    };
+    javy(&[
+        "init-plugin",
+        "--asyncify-imports",
+        "some_host.get_value",
+        uninitialized_plugin.to_str().unwrap(),
+        "-o",
+        plugin_path.to_str().unwrap(),
+    ])?;
+    javy(&[
+        "build",
+        "-C",
+        "dynamic",
+        "-C",
+        &format!("plugin={}", plugin_path.display()),
+        js_path.to_str().unwrap(),
+        "-o",
+        module_path.to_str().unwrap(),
+    ])?;
+
+    let engine = Engine::default();
+    let mut linker = Linker::new(&engine);
+    wasmtime_wasi::p1::add_to_linker_sync(&mut linker, |host: &mut AsyncifyHost| &mut host.wasi)?;
+    // Suspends the plugin the first time `get_value` is called by unwinding
+    // its stack, then returns the value when the stack is rewound.
+    linker.func_wrap(
+        "some_host",
+        "get_value",
+        |mut caller: Caller<'_, AsyncifyHost>| -> wasmtime::Result<u32> {
+            if caller.data().rewinding {
+                caller.data_mut().rewinding = false;
+                let stop_rewind = asyncify_export(&mut caller, "asyncify_stop_rewind");
+                stop_rewind
+                    .typed::<(), ()>(&caller)?
+                    .call(&mut caller, ())?;
+                return Ok(42);
+            }
+            let data = caller.data().asyncify_data;
+            let start_unwind = asyncify_export(&mut caller, "asyncify_start_unwind");
+            start_unwind
+                .typed::<i32, ()>(&caller)?
+                .call(&mut caller, data)?;
+            caller.data_mut().unwound = true;
+            // Ignored while unwinding.
+            Ok(0)
+        },
+    )?;
+    let plugin = Module::from_file(&engine, &plugin_path)?;
+    let module = Module::from_file(&engine, &module_path)?;
+    linker.define_unknown_imports_as_traps(&plugin)?;
+
+    let stdout = MemoryOutputPipe::new(usize::MAX);
+    let wasi = WasiCtxBuilder::new().stdout(stdout.clone()).build_p1();
+    let mut store = Store::new(
+        &engine,
+        AsyncifyHost {
+            wasi,
+            asyncify_data: 0,
+            unwound: false,
+            rewinding: false,
+        },
+    );
+    let plugin_instance = linker.instantiate(store.as_context_mut(), &plugin)?;
+    linker.instance(
+        store.as_context_mut(),
+        "test-plugin-wasip1",
+        plugin_instance,
+    )?;
+    let instance = linker.instantiate(store.as_context_mut(), &module)?;
+
+    // The asyncify data structure is the start and end of the buffer storing
+    // the unwound stack, followed by the buffer.
+    let memory = plugin_instance
+        .get_memory(store.as_context_mut(), "memory")
+        .expect("memory export");
+    let stack_size = 64 * 1024;
+    let data = plugin_instance
+        .get_typed_func::<(i32, i32, i32, i32), i32>(store.as_context_mut(), "cabi_realloc")?
+        .call(store.as_context_mut(), (0, 0, 4, 8 + stack_size))?;
+    let mut header = (data + 8).to_le_bytes().to_vec();
+    header.extend_from_slice(&(data + 8 + stack_size).to_le_bytes());
+    memory.write(store.as_context_mut(), data as usize, &header)?;
+    store.data_mut().asyncify_data = data;
+
+    // `_start` returns once the plugin's `invoke` is unwound.
+    instance
+        .get_typed_func::<(), ()>(store.as_context_mut(), "_start")?
+        .call(store.as_context_mut(), ())?;
+    assert!(store.data().unwound);
+    assert!(stdout.contents().is_empty());
+    plugin_instance
+        .get_typed_func::<(), ()>(store.as_context_mut(), "asyncify_stop_unwind")?
+        .call(store.as_context_mut(), ())?;
+
+    // Resume `invoke` where it was suspended. Its arguments are restored from
+    // the unwound stack.
+    plugin_instance
+        .get_typed_func::<i32, ()>(store.as_context_mut(), "asyncify_start_rewind")?
+        .call(store.as_context_mut(), data)?;
+    store.data_mut().rewinding = true;
+    plugin_instance
+        .get_typed_func::<(i32, i32, i32, i32, i32), ()>(store.as_context_mut(), "invoke")?
+        .call(store.as_context_mut(), (0, 0, 0, 0, 0))?;
+    assert!(!store.data().rewinding);
+    assert_eq!("42\n", str::from_utf8(&stdout.contents())?);
+    Ok(())
+}
+
+struct AsyncifyHost {
+    wasi: WasiP1Ctx,
+    /// The address of the asyncify data structure.
+    asyncify_data: i32,
+    /// Whether the plugin's stack was unwound.
+    unwound: bool,
+    /// Whether the plugin's stack is being rewound.
+    rewinding: bool,
+}
+
+fn asyncify_export(caller: &mut Caller<'_, AsyncifyHost>, name: &str) -> Func {
+    caller
+        .get_export(name)
+        .and_then(|export| export.into_func())
+        .unwrap_or_else(|| panic!("missing {name} export"))
+}
+
 fn run_with_u8s(r: &mut Runner, stdin: u8) -> (u8, String, u64) {
     let (output, logs, fuel_consumed) = run(r, stdin.to_le_bytes().into());
     assert_eq!(1, output.len());
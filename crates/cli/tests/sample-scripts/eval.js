@@ -0,0 +1,7 @@
+let supported;
+try {
+  supported = eval("true");
+} catch {
+  supported = false;
+}
+console.error(`eval supported: ${supported}`);
@@ -0,0 +1,2 @@
+console.log("hello");
+Javy.exitCode = 4;
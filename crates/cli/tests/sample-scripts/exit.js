@@ -0,0 +1,7 @@
+console.log("before exit");
+try {
+  Javy.exit(3);
+} catch (e) {
+  console.log("caught exit");
+}
+console.log("after exit");
@@ -0,0 +1,5 @@
+const objects = [];
+for (let i = 0; i < 10_000_000; i++) {
+  objects.push({ i });
+}
+console.log(objects.length);
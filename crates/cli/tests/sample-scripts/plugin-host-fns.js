@@ -0,0 +1,22 @@
+if (byteLength(new Uint8Array([1, 2, 3])) !== 3) {
+    throw new Error("Unexpected length of Uint8Array");
+}
+if (byteLength(new ArrayBuffer(4)) !== 4) {
+    throw new Error("Unexpected length of ArrayBuffer");
+}
+if (greet("Javy") !== "Hello, Javy!") {
+    throw new Error("Unexpected greeting");
+}
+const point = translate({ x: 1, y: 2 }, 3);
+if (point.x !== 4 || point.y !== 2) {
+    throw new Error("Unexpected point");
+}
+let message;
+try {
+    translate({ x: 1, y: 2 }, -1);
+} catch (e) {
+    message = e.message;
+}
+if (message !== "Offset must not be negative") {
+    throw new Error(`Unexpected error: ${message}`);
+}
@@ -1,28 +1,82 @@
+use std::io::Cursor;
+
 use anyhow::{Result, anyhow, bail};
+use brotli::enc::{self, BrotliEncoderParams};
 use wasmtime::{AsContext, AsContextMut, Engine, Instance, Linker, Memory, Module, Store};
 
 use crate::Plugin;
 
-pub(crate) fn compile_source(plugin: &Plugin, js_source_code: &[u8]) -> Result<Vec<u8>> {
-    let (mut store, instance, memory) = create_wasm_env(plugin.as_bytes())?;
-    let (js_src_ptr, js_src_len) =
-        copy_source_code_into_instance(js_source_code, store.as_context_mut(), &instance, &memory)?;
-    let ret_ptr = call_compile(js_src_ptr, js_src_len, store.as_context_mut(), &instance)?;
-    let bytecode = copy_bytecode_from_instance(ret_ptr, store.as_context_mut(), &memory)?;
-    Ok(bytecode)
+/// Compiles JavaScript source code to QuickJS bytecode using a plugin.
+///
+/// The plugin is compiled once and a fresh instance is created for each
+/// source so a single compiler can be shared across threads.
+pub(crate) struct Compiler {
+    engine: Engine,
+    module: Module,
 }
 
-fn create_wasm_env(plugin_bytes: &[u8]) -> Result<(Store<()>, Instance, Memory)> {
-    let engine = Engine::default();
-    let module = Module::new(&engine, plugin_bytes)?;
-    let mut linker = Linker::new(&engine);
-    let mut store = Store::new(&engine, ());
-    linker.define_unknown_imports_as_default_values(&mut store, &module)?;
-    let instance = linker.instantiate(store.as_context_mut(), &module)?;
-    let memory = instance
-        .get_memory(store.as_context_mut(), "memory")
-        .unwrap();
-    Ok((store, instance, memory))
+impl Compiler {
+    /// Create a new [`Compiler`] for the given plugin.
+    pub(crate) fn new(plugin: &Plugin) -> Result<Self> {
+        if !plugin.capabilities().compile_src {
+            bail!("Plugin can't compile JavaScript because it doesn't export `compile-src`");
+        }
+        let engine = Engine::default();
+        let module = Module::new(&engine, plugin.as_bytes())?;
+        Ok(Self { engine, module })
+    }
+
+    /// Compile the JavaScript source code to bytecode.
+    pub(crate) fn compile(&self, js_source_code: &[u8]) -> Result<Vec<u8>> {
+        let (mut store, instance, memory) = self.create_wasm_env()?;
+        let (js_src_ptr, js_src_len) = copy_source_code_into_instance(
+            js_source_code,
+            store.as_context_mut(),
+            &instance,
+            &memory,
+        )?;
+        let ret_ptr = call_compile(js_src_ptr, js_src_len, store.as_context_mut(), &instance)?;
+        let bytecode = copy_bytecode_from_instance(ret_ptr, store.as_context_mut(), &memory)?;
+        Ok(bytecode)
+    }
+
+    fn create_wasm_env(&self) -> Result<(Store<()>, Instance, Memory)> {
+        let mut linker = Linker::new(&self.engine);
+        let mut store = Store::new(&self.engine, ());
+        linker.define_unknown_imports_as_default_values(&mut store, &self.module)?;
+        let instance = linker.instantiate(store.as_context_mut(), &self.module)?;
+        let memory = instance
+            .get_memory(store.as_context_mut(), "memory")
+            .unwrap();
+        Ok((store, instance, memory))
+    }
+}
+
+/// The prefix of the header added to the bytecode of dynamically linked
+/// modules. Must match the prefix expected by `javy-plugin-api`.
+const HEADER_MAGIC: &[u8] = b"\xffjavy";
+
+/// Prefixes bytecode with a header containing the bytecode version, which
+/// plugins check before evaluating the bytecode.
+pub(crate) fn add_header(bytecode: &[u8], version: u32) -> Vec<u8> {
+    let mut with_header = HEADER_MAGIC.to_vec();
+    with_header.extend_from_slice(&version.to_le_bytes());
+    with_header.extend_from_slice(bytecode);
+    with_header
+}
+
+/// Compresses bytecode with Brotli for plugins exporting `invoke-compressed`.
+pub(crate) fn compress(bytecode: &[u8]) -> Result<Vec<u8>> {
+    let mut compressed = vec![];
+    enc::BrotliCompress(
+        &mut Cursor::new(bytecode),
+        &mut compressed,
+        &BrotliEncoderParams {
+            quality: 11,
+            ..Default::default()
+        },
+    )?;
+    Ok(compressed)
 }
 
 fn copy_source_code_into_instance(
@@ -0,0 +1,117 @@
+//! On-disk cache of pre-initialized plugins.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{ErrorKind, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+
+/// A content-addressed cache of plugins pre-initialized by Wizer.
+///
+/// Entries are keyed by everything that affects the output of the
+/// pre-initialization: the plugin, the JS runtime config, whether
+/// pre-initialization is deterministic, and the version of this crate.
+#[derive(Debug)]
+pub(crate) struct PluginCache {
+    /// The path of the file for this cache entry.
+    path: PathBuf,
+}
+
+impl PluginCache {
+    /// Create the cache entry in `dir` for the given inputs.
+    pub(crate) fn new(
+        dir: PathBuf,
+        plugin: &[u8],
+        js_runtime_config: &Map<String, Value>,
+        deterministic: bool,
+    ) -> Result<Self> {
+        // The config is serialized with sorted keys so the order the options
+        // were given in doesn't change the key.
+        let js_runtime_config =
+            serde_json::to_vec(&js_runtime_config.iter().collect::<BTreeMap<_, _>>())?;
+        let deterministic = [deterministic as u8];
+        let mut hasher = Sha256::new();
+        for input in [
+            env!("CARGO_PKG_VERSION").as_bytes(),
+            plugin,
+            &js_runtime_config,
+            &deterministic,
+        ] {
+            // Length prefixes ensure different inputs can't produce the same
+            // hashed bytes.
+            hasher.update((input.len() as u64).to_le_bytes());
+            hasher.update(input);
+        }
+        let key = format!("{:x}", hasher.finalize());
+        Ok(Self {
+            path: dir.join(key).with_extension("wasm"),
+        })
+    }
+
+    /// Read the cached pre-initialized plugin, if present.
+    pub(crate) fn get(&self) -> Result<Option<Vec<u8>>> {
+        match fs::read(&self.path) {
+            Ok(wasm) => Ok(Some(wasm)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e)
+                .with_context(|| format!("Failed to read cache entry {}", self.path.display())),
+        }
+    }
+
+    /// Store the pre-initialized plugin.
+    ///
+    /// The entry is written to a temporary file which is then moved into
+    /// place so concurrent builds never observe a partially written entry.
+    pub(crate) fn put(&self, wasm: &[u8]) -> Result<()> {
+        let dir = self.path.parent().expect("cache entry to have a parent");
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+        let mut file = NamedTempFile::new_in(dir)?;
+        file.write_all(wasm)?;
+        file.persist(&self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PluginCache;
+    use anyhow::Result;
+    use serde_json::{Map, Value};
+
+    #[test]
+    fn entries_are_keyed_by_inputs() -> Result<()> {
+        let path = |plugin: &[u8], config: &str, deterministic| -> Result<_> {
+            let config: Map<String, Value> = serde_json::from_str(config)?;
+            Ok(PluginCache::new("cache".into(), plugin, &config, deterministic)?.path)
+        };
+
+        let entry = path(b"plugin", "{}", false)?;
+        assert_eq!(entry, path(b"plugin", "{}", false)?);
+        assert_ne!(entry, path(b"plugin2", "{}", false)?);
+        assert_ne!(entry, path(b"plugin", r#"{"eval":false}"#, false)?);
+        assert_ne!(entry, path(b"plugin", "{}", true)?);
+        assert_eq!(
+            path(b"plugin", r#"{"eval":false,"text-encoding":true}"#, false)?,
+            path(b"plugin", r#"{"text-encoding":true,"eval":false}"#, false)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn get_and_put() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache = PluginCache::new(dir.path().join("nested"), b"plugin", &Map::new(), false)?;
+
+        assert_eq!(None, cache.get()?);
+        cache.put(b"wasm")?;
+        assert_eq!(Some(b"wasm".to_vec()), cache.get()?);
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+//! Information recorded in generated modules.
+
+use std::borrow::Cow;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use walrus::{CustomSection, IdsToIndices, RawCustomSection};
+
+use crate::{Plugin, transform};
+
+/// The name of the custom section containing the [`BytecodeInfo`].
+const BYTECODE_SECTION_NAME: &str = "javy_bytecode";
+
+/// The name of the custom section containing the [`JavyConfig`].
+const CONFIG_SECTION_NAME: &str = "javy_config";
+
+/// The contents of the custom section named `name` in a module, if any.
+fn custom_section(wasm: &[u8], name: &str) -> Result<Option<Vec<u8>>> {
+    let module = transform::module_config().parse(wasm)?;
+    Ok(module
+        .customs
+        .iter()
+        .find(|(_, section)| section.name() == name)
+        .map(|(_, section)| section.data(&Default::default()).into_owned()))
+}
+
+/// The version of the bytecode format and fingerprint of the plugin a
+/// dynamically linked module was generated with.
+///
+/// Only recorded for modules generated with plugins that declare their
+/// bytecode version, which the plugin then checks when evaluating the
+/// bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytecodeInfo {
+    /// The version of the bytecode format.
+    pub version: u32,
+    /// The SHA-256 digest of the plugin, as a hex string.
+    pub plugin_fingerprint: String,
+}
+
+impl BytecodeInfo {
+    /// Read the bytecode information recorded in a module, if any.
+    pub fn from_module(wasm: &[u8]) -> Result<Option<Self>> {
+        let Some(data) = custom_section(wasm, BYTECODE_SECTION_NAME)? else {
+            return Ok(None);
+        };
+        let Some((version, fingerprint)) = data.split_first_chunk::<4>() else {
+            bail!("Custom section `{BYTECODE_SECTION_NAME}` is truncated");
+        };
+        Ok(Some(Self {
+            version: u32::from_le_bytes(*version),
+            plugin_fingerprint: String::from_utf8(fingerprint.to_vec())?,
+        }))
+    }
+
+    /// Check the module can be run with `plugin`, which must be the plugin
+    /// the module was generated with.
+    pub fn check_plugin(&self, plugin: &Plugin) -> Result<()> {
+        match plugin.capabilities().bytecode_version {
+            None => bail!("Plugin doesn't declare the bytecode version it supports"),
+            Some(version) if version != self.version => bail!(
+                "Module uses bytecode version {} but the plugin supports version {version}",
+                self.version
+            ),
+            Some(_) => {}
+        }
+        let fingerprint = plugin.fingerprint();
+        if fingerprint != self.plugin_fingerprint {
+            bail!(
+                "Module was generated with a different plugin: expected fingerprint {} but \
+                the plugin's fingerprint is {fingerprint}",
+                self.plugin_fingerprint
+            );
+        }
+        Ok(())
+    }
+}
+
+impl CustomSection for BytecodeInfo {
+    fn name(&self) -> &str {
+        BYTECODE_SECTION_NAME
+    }
+
+    fn data(&self, _ids_to_indices: &IdsToIndices) -> Cow<'_, [u8]> {
+        let mut data = self.version.to_le_bytes().to_vec();
+        data.extend_from_slice(self.plugin_fingerprint.as_bytes());
+        data.into()
+    }
+}
+
+/// The configuration a module was generated with.
+///
+/// Recorded in every generated module so tools and hosts can tell which JS
+/// runtime options a module needs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JavyConfig {
+    /// The version of Javy that generated the module.
+    pub javy_version: String,
+    /// The import namespace of the plugin the module was generated with.
+    pub import_namespace: String,
+    /// The JS runtime config the plugin was initialized with, which is empty
+    /// if none was provided.
+    pub runtime_config: Map<String, Value>,
+}
+
+impl JavyConfig {
+    /// Read the configuration recorded in a module, if any.
+    pub fn from_module(wasm: &[u8]) -> Result<Option<Self>> {
+        custom_section(wasm, CONFIG_SECTION_NAME)?
+            .map(|data| Ok(serde_json::from_slice(&data)?))
+            .transpose()
+    }
+
+    /// Check the module can be linked against `plugin`.
+    pub fn check_plugin(&self, plugin: &Plugin) -> Result<()> {
+        let import_namespace = plugin.import_namespace()?;
+        if import_namespace != self.import_namespace {
+            bail!(
+                "Module imports from `{}` but the plugin's import namespace is `{import_namespace}`",
+                self.import_namespace
+            );
+        }
+        Ok(())
+    }
+
+    /// Create the custom section recording the configuration.
+    pub(crate) fn to_section(&self) -> Result<RawCustomSection> {
+        Ok(RawCustomSection {
+            name: CONFIG_SECTION_NAME.to_string(),
+            data: serde_json::to_vec(self)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use serde_json::json;
+    use walrus::{Module, ModuleConfig};
+
+    use super::{BytecodeInfo, JavyConfig};
+
+    #[test]
+    fn test_read_bytecode_info() -> Result<()> {
+        let info = BytecodeInfo {
+            version: 3,
+            plugin_fingerprint: "abc123".into(),
+        };
+        let mut module = Module::with_config(ModuleConfig::default());
+        assert_eq!(None, BytecodeInfo::from_module(&module.emit_wasm())?);
+
+        module.customs.add(info.clone());
+        assert_eq!(Some(info), BytecodeInfo::from_module(&module.emit_wasm())?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_javy_config() -> Result<()> {
+        let config = JavyConfig {
+            javy_version: "1.2.3".into(),
+            import_namespace: "my-plugin".into(),
+            runtime_config: json!({ "eval": false }).as_object().unwrap().clone(),
+        };
+        let mut module = Module::with_config(ModuleConfig::default());
+        assert_eq!(None, JavyConfig::from_module(&module.emit_wasm())?);
+
+        module.customs.add(config.to_section()?);
+        assert_eq!(Some(config), JavyConfig::from_module(&module.emit_wasm())?);
+        Ok(())
+    }
+}
@@ -17,7 +17,8 @@
 //! The generated Wasm module is self contained and the bytecode version matches
 //! the exact requirements of the embedded QuickJs engine. Use
 //! [`Generator::deterministic`] for reproducible builds (e.g., for verification
-//! or caching).
+//! or caching), and [`strip_unused`] to remove engine code the module can't
+//! call, such as the code of intrinsics disabled in the JS runtime config.
 //!
 //! ## Dynamic code generation
 //!
@@ -34,6 +35,11 @@
 //! as well as ensuring that any features available in the plugin match the
 //! features requsted by the JavaScript bytecode.
 //!
+//! When the plugin declares the version of the bytecode format it supports,
+//! the version and the plugin's fingerprint are recorded in the module and can
+//! be read with [`BytecodeInfo`], and the plugin reports an error instead of
+//! evaluating bytecode of a different version.
+//!
 //! ## Examples
 //!
 //! Simple Wasm module generation:
@@ -73,32 +79,46 @@
 //!   unstable API's exposed by this future may break in the future without
 //!   notice.
 
-use std::fs;
+use std::{num::NonZeroUsize, path::PathBuf, thread};
 
 pub(crate) mod bytecode;
+pub(crate) mod cache;
 pub(crate) mod exports;
+pub(crate) mod inspect;
 pub(crate) mod transform;
 
 pub(crate) mod js;
 pub(crate) mod plugin;
+pub(crate) mod strip;
 pub(crate) mod wit;
 
+use crate::bytecode::Compiler;
+use crate::cache::PluginCache;
 use crate::exports::Exports;
+pub use crate::inspect::{BytecodeInfo, JavyConfig};
 pub use crate::js::JS;
 pub use crate::plugin::Plugin;
+pub use crate::strip::{StrippedModule, strip_unused};
 pub use crate::wit::WitOptions;
+pub use javy_optimization::Optimization;
 
 use transform::SourceCodeSection;
 use walrus::{
-    DataId, DataKind, ExportItem, FunctionBuilder, FunctionId, LocalId, MemoryId, Module, ValType,
+    ConstExpr, DataId, DataKind, ExportItem, FunctionBuilder, FunctionId, GlobalId, LocalId,
+    MemoryId, Module, ValType,
+    ir::{UnaryOp, Value},
 };
-use wasm_opt::{OptimizationOptions, ShrinkLevel};
 use wasmtime::{Engine, Linker, Store};
-use wasmtime_wasi::{WasiCtxBuilder, p2::pipe::MemoryInputPipe};
+use wasmtime_wasi::{I32Exit, WasiCtxBuilder, p1::WasiP1Ctx, p2::pipe::MemoryInputPipe};
 
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
+use serde_json::Map;
 use wasmtime_wizer::Wizer;
 
+/// The name of the function export used to evaluate the top-level scope when
+/// pre-evaluating a module. It's removed from the module by Wizer.
+const PRE_EVALUATE_EXPORT: &str = "javy.pre-evaluate";
+
 /// The kind of linking to use.
 #[derive(Debug, Clone, Default)]
 pub enum LinkingKind {
@@ -128,14 +148,27 @@ pub(crate) struct Identifiers {
     cabi_realloc: FunctionId,
     invoke: FunctionId,
     memory: MemoryId,
+    /// The plugin's `call-export` function, if it's supported and used.
+    call_export: Option<FunctionId>,
+    /// The plugin's `configure-module` function, if the module has a runtime
+    /// config to apply.
+    configure_module: Option<FunctionId>,
 }
 
 impl Identifiers {
-    fn new(cabi_realloc: FunctionId, invoke: FunctionId, memory: MemoryId) -> Self {
+    fn new(
+        cabi_realloc: FunctionId,
+        invoke: FunctionId,
+        memory: MemoryId,
+        call_export: Option<FunctionId>,
+        configure_module: Option<FunctionId>,
+    ) -> Self {
         Self {
             cabi_realloc,
             invoke,
             memory,
+            call_export,
+            configure_module,
         }
     }
 }
@@ -147,14 +180,32 @@ pub(crate) struct BytecodeMetadata {
     ptr: LocalId,
     len: i32,
     data_section: DataId,
+    /// The function evaluating the top-level scope.
+    main: FunctionId,
+    /// Whether the top-level scope has been evaluated, when using
+    /// `call-export`.
+    evaluated: Option<GlobalId>,
+    /// The function passing the module's runtime config to the plugin, which
+    /// must be called before evaluating the bytecode.
+    configure: Option<FunctionId>,
 }
 
 impl BytecodeMetadata {
-    fn new(ptr: LocalId, len: i32, data_section: DataId) -> Self {
+    fn new(
+        ptr: LocalId,
+        len: i32,
+        data_section: DataId,
+        main: FunctionId,
+        evaluated: Option<GlobalId>,
+        configure: Option<FunctionId>,
+    ) -> Self {
         Self {
             ptr,
             len,
             data_section,
+            main,
+            evaluated,
+            configure,
         }
     }
 }
@@ -170,14 +221,25 @@ pub struct Generator {
     pub(crate) source_embedding: SourceEmbedding,
     /// WIT options for code generation.
     pub(crate) wit_opts: WitOptions,
-    /// JavaScript function exports.
-    pub(crate) function_exports: Exports,
     /// An optional JS runtime config provided as JSON bytes.
     js_runtime_config: Vec<u8>,
     /// The version string to include in the producers custom section.
     producer_version: Option<String>,
     /// Whether to use fixed clocks for deterministic builds.
     deterministic: bool,
+    /// An optional directory in which to cache pre-initialized plugins.
+    cache_dir: Option<PathBuf>,
+    /// Whether to evaluate the top-level scope at build time.
+    pre_evaluate: bool,
+    /// The `wasm-opt` optimizations to run on statically linked modules.
+    optimization: Optimization,
+    /// An optional plugin to embed in statically linked modules instead of
+    /// `plugin`.
+    embedded_plugin: Option<Plugin>,
+    /// Whether to keep function names in a `name` custom section.
+    debug_info: bool,
+    /// Whether to compress the bytecode embedded in the generated module.
+    compress_bytecode: bool,
 }
 
 impl Generator {
@@ -209,6 +271,10 @@ impl Generator {
 
     #[cfg(feature = "plugin_internal")]
     /// Set the JS runtime configuration options to pass to the module.
+    ///
+    /// Statically linked modules embed a plugin initialized with the options.
+    /// Dynamically linked modules pass them to the plugin's
+    /// `configure-module` export before evaluating the bytecode.
     pub fn js_runtime_config(&mut self, js_runtime_config: Vec<u8>) -> &mut Self {
         self.js_runtime_config = js_runtime_config;
         self
@@ -226,28 +292,145 @@ impl Generator {
         self.deterministic = deterministic;
         self
     }
+
+    /// Set a directory in which to cache the plugin pre-initialized for
+    /// static linking, so builds sharing the same plugin, JS runtime config
+    /// and determinism setting skip pre-initialization. (default: no cache)
+    pub fn cache_dir(&mut self, cache_dir: PathBuf) -> &mut Self {
+        self.cache_dir = Some(cache_dir);
+        self
+    }
+
+    /// Evaluate the top-level scope of the JS at build time and snapshot the
+    /// result into the generated module, so `_start` and exported functions
+    /// don't load and evaluate the bytecode when called. Only supported with
+    /// static linking. (default: false)
+    ///
+    /// Side effects of the top-level scope, such as writing to stdout, happen
+    /// at build time instead of when the generated module runs. State created
+    /// by the top-level scope is the same in every instance of the module, so
+    /// values it computes with `Date.now()` are from build time and, if it
+    /// calls `Math.random()`, the random number generator returns the same
+    /// sequence of numbers in every instance.
+    /// Generating the module fails if the top-level scope calls `Javy.exit` or
+    /// sets `Javy.exitCode` to a non-zero value.
+    pub fn pre_evaluate(&mut self, pre_evaluate: bool) -> &mut Self {
+        self.pre_evaluate = pre_evaluate;
+        self
+    }
+
+    /// Set the `wasm-opt` optimizations to run on statically linked modules.
+    /// (default: [`Optimization::Speed`])
+    pub fn optimization(&mut self, optimization: Optimization) -> &mut Self {
+        self.optimization = optimization;
+        self
+    }
+
+    /// Set the plugin to embed in statically linked modules, for example a
+    /// plugin built with the `bytecode-only` feature of `javy-plugin-api`
+    /// which can execute bytecode but can't compile JS. The JS is still
+    /// compiled with the plugin passed to [`Generator::new`], so both plugins
+    /// must use the same version of QuickJS. (default: the plugin passed to
+    /// [`Generator::new`])
+    pub fn embedded_plugin(&mut self, plugin: Plugin) -> &mut Self {
+        self.embedded_plugin = Some(plugin);
+        self
+    }
+
+    /// Keep the function names of the plugin in a `name` custom section of
+    /// the generated module so traps and profilers can show them. Plugins
+    /// initialized without debug info don't contain function names.
+    /// (default: false)
+    pub fn debug_info(&mut self, debug_info: bool) -> &mut Self {
+        self.debug_info = debug_info;
+        self
+    }
+
+    /// Compress the bytecode embedded in the generated module with Brotli.
+    /// The plugin decompresses it before evaluating it, so modules are
+    /// smaller at the cost of a slower start. Requires a plugin exporting
+    /// `invoke-compressed`, such as the default plugin. (default: false)
+    pub fn compress_bytecode(&mut self, compress_bytecode: bool) -> &mut Self {
+        self.compress_bytecode = compress_bytecode;
+        self
+    }
 }
 
 impl Generator {
-    /// Generate the starting module.
-    async fn generate_initial_module(&self) -> Result<Module> {
-        let config = transform::module_config();
-        let module = match &self.linking {
+    /// The plugin to embed in statically linked modules.
+    fn static_plugin(&self) -> &Plugin {
+        self.embedded_plugin.as_ref().unwrap_or(&self.plugin)
+    }
+
+    /// The bytecode version to check when evaluating the bytecode.
+    ///
+    /// Only dynamically linked modules are checked since statically linked
+    /// modules embed the plugin the bytecode was compiled for.
+    fn bytecode_version(&self) -> Option<u32> {
+        match self.linking {
+            LinkingKind::Static => None,
+            LinkingKind::Dynamic => self.plugin.capabilities().bytecode_version,
+        }
+    }
+
+    /// The JS runtime config, which is empty if none was provided.
+    fn runtime_config(&self) -> Result<Map<String, serde_json::Value>> {
+        if self.js_runtime_config.is_empty() {
+            return Ok(Map::new());
+        }
+        Ok(serde_json::from_slice(&self.js_runtime_config)?)
+    }
+
+    /// The configuration to record in the generated module.
+    fn javy_config(&self, javy_version: &str) -> Result<JavyConfig> {
+        Ok(JavyConfig {
+            javy_version: javy_version.to_string(),
+            import_namespace: self.plugin.import_namespace()?,
+            runtime_config: self.runtime_config()?,
+        })
+    }
+
+    /// The name of the plugin function used to evaluate the bytecode.
+    fn invoke_name(&self, plugin: &Plugin) -> Result<&'static str> {
+        if !self.compress_bytecode {
+            return Ok("invoke");
+        }
+        if !plugin.capabilities().invoke_compressed {
+            bail!(
+                "Plugin can't evaluate compressed bytecode because it doesn't export `invoke-compressed`"
+            );
+        }
+        Ok("invoke-compressed")
+    }
+
+    /// Pre-initialize the plugin for static linking.
+    ///
+    /// Returns `None` when using dynamic linking since the plugin isn't
+    /// embedded in the generated module.
+    async fn initialize_plugin(&self) -> Result<Option<Vec<u8>>> {
+        let wasm = match &self.linking {
             LinkingKind::Static => {
-                let engine = Engine::default();
-                let mut builder = WasiCtxBuilder::new();
-                builder
-                    .stdin(MemoryInputPipe::new(self.js_runtime_config.clone()))
-                    .inherit_stdout()
-                    .inherit_stderr();
-                if self.deterministic {
-                    deterministic_wasi_ctx::add_determinism_to_wasi_ctx_builder(&mut builder);
+                let plugin = self.static_plugin();
+                let cache = self
+                    .cache_dir
+                    .clone()
+                    .map(|dir| {
+                        PluginCache::new(
+                            dir,
+                            plugin.as_bytes(),
+                            &self.runtime_config()?,
+                            self.deterministic,
+                        )
+                    })
+                    .transpose()?;
+                if let Some(wasm) = cache.as_ref().map(PluginCache::get).transpose()?.flatten() {
+                    return Ok(Some(wasm));
                 }
-                let wasi = builder.build_p1();
-                let mut store = Store::new(&engine, wasi);
+
+                let mut store = self.wizer_store(self.js_runtime_config.clone());
                 let wasm = Wizer::new()
                     .init_func("initialize-runtime")
-                    .run(&mut store, self.plugin.as_bytes(), async |store, module| {
+                    .run(&mut store, plugin.as_bytes(), async |store, module| {
                         let engine = store.engine();
                         let mut linker = Linker::new(engine);
                         wasmtime_wasi::p1::add_to_linker_async(&mut linker, |cx| cx)?;
@@ -256,9 +439,65 @@ impl Generator {
                         Ok(instance)
                     })
                     .await?;
-                config.parse(&wasm)?
+                if let Some(cache) = &cache {
+                    cache.put(&wasm)?;
+                }
+                Some(wasm)
             }
-            LinkingKind::Dynamic => Module::with_config(config),
+            LinkingKind::Dynamic => None,
+        };
+        Ok(wasm)
+    }
+
+    /// Pre-evaluate the top-level scope of a generated module if
+    /// pre-evaluation is enabled.
+    async fn pre_evaluate_module(&self, wasm: Vec<u8>) -> Result<Vec<u8>> {
+        if !self.pre_evaluate {
+            return Ok(wasm);
+        }
+        let mut store = self.wizer_store(vec![]);
+        let wasm = Wizer::new()
+            .init_func(PRE_EVALUATE_EXPORT)
+            .run(&mut store, &wasm, async |store, module| {
+                let engine = store.engine();
+                let mut linker = Linker::new(engine);
+                wasmtime_wasi::p1::add_to_linker_async(&mut linker, |cx| cx)?;
+                linker.define_unknown_imports_as_traps(module)?;
+                let instance = linker.instantiate_async(store, module).await?;
+                Ok(instance)
+            })
+            .await
+            .map_err(|e| match e.downcast_ref::<I32Exit>() {
+                Some(I32Exit(code)) => anyhow!(
+                    "The top-level scope exited with code {code} while being pre-evaluated, `Javy.exit` and `Javy.exitCode` can't be used in the top-level scope of pre-evaluated modules"
+                ),
+                None => e.into(),
+            })?;
+        Ok(wasm)
+    }
+
+    /// Create a store for running Wizer with the given stdin.
+    fn wizer_store(&self, stdin: Vec<u8>) -> Store<WasiP1Ctx> {
+        let engine = Engine::default();
+        let mut builder = WasiCtxBuilder::new();
+        builder
+            .stdin(MemoryInputPipe::new(stdin))
+            .inherit_stdout()
+            .inherit_stderr();
+        if self.deterministic {
+            deterministic_wasi_ctx::add_determinism_to_wasi_ctx_builder(&mut builder);
+        }
+        let wasi = builder.build_p1();
+        Store::new(&engine, wasi)
+    }
+
+    /// Generate the starting module.
+    fn generate_initial_module(&self, initialized_plugin: Option<&[u8]>) -> Result<Module> {
+        let mut config = transform::module_config();
+        config.generate_name_section(self.debug_info);
+        let module = match initialized_plugin {
+            Some(wasm) => config.parse(wasm)?,
+            None => Module::with_config(config),
         };
         Ok(module)
     }
@@ -268,7 +507,9 @@ impl Generator {
         match self.linking {
             LinkingKind::Static => {
                 let cabi_realloc = module.exports.get_func("cabi_realloc")?;
-                let invoke = module.exports.get_func("invoke")?;
+                let invoke = module
+                    .exports
+                    .get_func(self.invoke_name(self.static_plugin())?)?;
                 let ExportItem::Memory(memory) = module
                     .exports
                     .iter()
@@ -278,7 +519,18 @@ impl Generator {
                 else {
                     anyhow::bail!("Export with name memory must be of type memory")
                 };
-                Ok(Identifiers::new(cabi_realloc, invoke, memory))
+                let call_export = if self.static_plugin().capabilities().call_export {
+                    Some(module.exports.get_func("call-export")?)
+                } else {
+                    None
+                };
+                Ok(Identifiers::new(
+                    cabi_realloc,
+                    invoke,
+                    memory,
+                    call_export,
+                    None,
+                ))
             }
             LinkingKind::Dynamic => {
                 // All code by default is assumed to be linking against a default
@@ -301,8 +553,11 @@ impl Generator {
                 ]
                 .as_slice();
                 let invoke_type = module.types.add(invoke_params, &[]);
-                let (invoke_fn_id, _) =
-                    module.add_import_func(&import_namespace, "invoke", invoke_type);
+                let (invoke_fn_id, _) = module.add_import_func(
+                    &import_namespace,
+                    self.invoke_name(&self.plugin)?,
+                    invoke_type,
+                );
 
                 let (memory_id, _) = module.add_import_memory(
                     &import_namespace,
@@ -314,10 +569,36 @@ impl Generator {
                     None,
                 );
 
+                // Statically linked modules embed a plugin initialized with
+                // the runtime config, but dynamically linked modules share the
+                // plugin so it's passed to the plugin when the module is run.
+                let configure_module_fn_id = if self.runtime_config()?.is_empty() {
+                    None
+                } else {
+                    if !self.plugin.capabilities().configure_module {
+                        bail!(
+                            "Plugin can't apply the JavaScript runtime config of dynamically linked modules because it doesn't export `configure-module`"
+                        );
+                    }
+                    let configure_module_type =
+                        module.types.add(&[ValType::I32, ValType::I32], &[]);
+                    let (configure_module_fn_id, _) = module.add_import_func(
+                        &import_namespace,
+                        "configure-module",
+                        configure_module_type,
+                    );
+                    Some(configure_module_fn_id)
+                };
+
+                // Dynamically linked modules don't import `call-export` so they
+                // keep working with plugins using the same import namespace
+                // that don't export it.
                 Ok(Identifiers::new(
                     cabi_realloc_fn_id,
                     invoke_fn_id,
                     memory_id,
+                    None,
+                    configure_module_fn_id,
                 ))
             }
         }
@@ -329,14 +610,36 @@ impl Generator {
         module: &mut Module,
         js: &js::JS,
         imports: &Identifiers,
+        compiler: &Compiler,
     ) -> Result<BytecodeMetadata> {
-        let bytecode = bytecode::compile_source(&self.plugin, js.as_bytes())?;
+        let mut bytecode = compiler.compile(js.as_bytes())?;
+        if let Some(version) = self.bytecode_version() {
+            bytecode = bytecode::add_header(&bytecode, version);
+        }
+        if self.compress_bytecode {
+            bytecode = bytecode::compress(&bytecode)?;
+        }
         let bytecode_len: i32 = bytecode.len().try_into()?;
         let bytecode_data = module.data.add(DataKind::Passive, bytecode);
+        // Tracks whether the top-level scope has been evaluated so exported
+        // functions using `call-export` only evaluate it once.
+        let evaluated = imports.call_export.map(|_| {
+            module
+                .globals
+                .add_local(ValType::I32, true, false, ConstExpr::Value(Value::I32(0)))
+        });
+
+        let configure = imports
+            .configure_module
+            .map(|configure_module| self.generate_configure(module, imports, configure_module))
+            .transpose()?;
 
         let mut main = FunctionBuilder::new(&mut module.types, &[], &[]);
         let bytecode_ptr_local = module.locals.add(ValType::I32);
         let mut instructions = main.func_body();
+        if let Some(configure) = configure {
+            instructions.call(configure);
+        }
         instructions
             // Allocate memory in plugin instance for bytecode array.
             .i32_const(0) // orig ptr
@@ -358,64 +661,183 @@ impl Generator {
             .i32_const(0) // set function name ptr to null
             .i32_const(0) // set function name len to 0
             .call(imports.invoke);
+        if let Some(evaluated) = evaluated {
+            instructions.i32_const(1).global_set(evaluated);
+        }
         let main = main.finish(vec![], &mut module.funcs);
 
-        module.exports.add("_start", main);
+        if self.pre_evaluate {
+            // The top-level scope is evaluated by Wizer so there's nothing
+            // left for `_start` to do.
+            module.exports.add(PRE_EVALUATE_EXPORT, main);
+            let start = FunctionBuilder::new(&mut module.types, &[], &[]);
+            let start = start.finish(vec![], &mut module.funcs);
+            module.exports.add("_start", start);
+        } else {
+            module.exports.add("_start", main);
+        }
         Ok(BytecodeMetadata::new(
             bytecode_ptr_local,
             bytecode_len,
             bytecode_data,
+            main,
+            evaluated,
+            configure,
         ))
     }
 
+    /// Generate a function copying the runtime config into the plugin
+    /// instance's memory and passing it to `configure-module`.
+    fn generate_configure(
+        &self,
+        module: &mut Module,
+        imports: &Identifiers,
+        configure_module: FunctionId,
+    ) -> Result<FunctionId> {
+        let config = serde_json::to_vec(&self.runtime_config()?)?;
+        let config_len: i32 = config.len().try_into()?;
+        let config_data = module.data.add(DataKind::Passive, config);
+        // Tracks whether the runtime has been configured so it's only
+        // reconfigured once per instance, since `_start` and each exported
+        // function configure it.
+        let configured =
+            module
+                .globals
+                .add_local(ValType::I32, true, false, ConstExpr::Value(Value::I32(0)));
+
+        let mut configure = FunctionBuilder::new(&mut module.types, &[], &[]);
+        let config_ptr_local = module.locals.add(ValType::I32);
+        configure.func_body().global_get(configured).if_else(
+            None,
+            |_| {},
+            |else_| {
+                else_
+                    .i32_const(1)
+                    .global_set(configured)
+                    // Allocate memory in plugin instance for the config.
+                    .i32_const(0) // orig ptr
+                    .i32_const(0) // orig size
+                    .i32_const(1) // alignment
+                    .i32_const(config_len) // new size
+                    .call(imports.cabi_realloc)
+                    // Copy the config into allocated memory.
+                    .local_tee(config_ptr_local)
+                    .i32_const(0) // offset into data segment
+                    .i32_const(config_len) // size to copy
+                    .memory_init(imports.memory, config_data)
+                    .local_get(config_ptr_local)
+                    .i32_const(config_len)
+                    .call(configure_module);
+            },
+        );
+        Ok(configure.finish(vec![], &mut module.funcs))
+    }
+
     /// Generate function exports.
     fn generate_exports(
         &self,
         module: &mut Module,
         identifiers: &Identifiers,
         bc_metadata: &BytecodeMetadata,
+        function_exports: &Exports,
     ) -> Result<()> {
-        if !self.function_exports.is_empty() {
+        if !function_exports.is_empty() {
             let fn_name_ptr_local = module.locals.add(ValType::I32);
-            for export in &self.function_exports {
+            for export in function_exports {
                 // For each JS function export, add an export that copies the name of the function into memory and invokes it.
                 let js_export_bytes = export.js.as_bytes();
                 let js_export_len: i32 = js_export_bytes.len().try_into().unwrap();
                 let fn_name_data = module.data.add(DataKind::Passive, js_export_bytes.to_vec());
 
-                let mut export_fn = FunctionBuilder::new(&mut module.types, &[], &[]);
-                export_fn
-                    .func_body()
-                    // Copy bytecode.
-                    .i32_const(0) // orig ptr
-                    .i32_const(0) // orig len
-                    .i32_const(1) // alignment
-                    .i32_const(bc_metadata.len) // size to copy
-                    .call(identifiers.cabi_realloc)
-                    .local_tee(bc_metadata.ptr)
-                    .i32_const(0) // offset into data segment
-                    .i32_const(bc_metadata.len) // size to copy
-                    .memory_init(identifiers.memory, bc_metadata.data_section) // copy bytecode into allocated memory
-                    .data_drop(bc_metadata.data_section)
-                    // Copy function name.
-                    .i32_const(0) // orig ptr
-                    .i32_const(0) // orig len
-                    .i32_const(1) // alignment
-                    .i32_const(js_export_len) // new size
-                    .call(identifiers.cabi_realloc)
-                    .local_tee(fn_name_ptr_local)
-                    .i32_const(0) // offset into data segment
-                    .i32_const(js_export_len) // size to copy
-                    .memory_init(identifiers.memory, fn_name_data) // copy fn name into allocated memory
-                    .data_drop(fn_name_data)
-                    // Call invoke.
-                    .local_get(bc_metadata.ptr)
-                    .i32_const(bc_metadata.len)
-                    .i32_const(1) // set function name option discriminator to some
-                    .local_get(fn_name_ptr_local)
-                    .i32_const(js_export_len)
-                    .call(identifiers.invoke);
-                let export_fn = export_fn.finish(vec![], &mut module.funcs);
+                let export_fn = match (identifiers.call_export, bc_metadata.evaluated) {
+                    (Some(call_export), Some(evaluated)) => {
+                        // Keeps the address of the function name so it's only
+                        // copied into memory on the first call.
+                        let fn_name_ptr = module.globals.add_local(
+                            ValType::I32,
+                            true,
+                            false,
+                            ConstExpr::Value(Value::I32(0)),
+                        );
+                        let mut export_fn = FunctionBuilder::new(&mut module.types, &[], &[]);
+                        let mut instructions = export_fn.func_body();
+                        if !self.pre_evaluate {
+                            // Evaluate the top-level scope on the first call.
+                            instructions
+                                .global_get(evaluated)
+                                .unop(UnaryOp::I32Eqz)
+                                .if_else(
+                                    None,
+                                    |then| {
+                                        then.call(bc_metadata.main);
+                                    },
+                                    |_| {},
+                                );
+                        }
+                        instructions
+                            .global_get(fn_name_ptr)
+                            .unop(UnaryOp::I32Eqz)
+                            .if_else(
+                                None,
+                                |then| {
+                                    then.i32_const(0) // orig ptr
+                                        .i32_const(0) // orig len
+                                        .i32_const(1) // alignment
+                                        .i32_const(js_export_len) // new size
+                                        .call(identifiers.cabi_realloc)
+                                        .global_set(fn_name_ptr)
+                                        .global_get(fn_name_ptr)
+                                        .i32_const(0) // offset into data segment
+                                        .i32_const(js_export_len) // size to copy
+                                        .memory_init(identifiers.memory, fn_name_data); // copy fn name into allocated memory
+                                },
+                                |_| {},
+                            )
+                            // Call the function on the evaluated module.
+                            .global_get(fn_name_ptr)
+                            .i32_const(js_export_len)
+                            .call(call_export);
+                        export_fn.finish(vec![], &mut module.funcs)
+                    }
+                    _ => {
+                        let mut export_fn = FunctionBuilder::new(&mut module.types, &[], &[]);
+                        let mut instructions = export_fn.func_body();
+                        if let Some(configure) = bc_metadata.configure {
+                            instructions.call(configure);
+                        }
+                        instructions
+                            // Copy bytecode.
+                            .i32_const(0) // orig ptr
+                            .i32_const(0) // orig len
+                            .i32_const(1) // alignment
+                            .i32_const(bc_metadata.len) // size to copy
+                            .call(identifiers.cabi_realloc)
+                            .local_tee(bc_metadata.ptr)
+                            .i32_const(0) // offset into data segment
+                            .i32_const(bc_metadata.len) // size to copy
+                            .memory_init(identifiers.memory, bc_metadata.data_section) // copy bytecode into allocated memory
+                            .data_drop(bc_metadata.data_section)
+                            // Copy function name.
+                            .i32_const(0) // orig ptr
+                            .i32_const(0) // orig len
+                            .i32_const(1) // alignment
+                            .i32_const(js_export_len) // new size
+                            .call(identifiers.cabi_realloc)
+                            .local_tee(fn_name_ptr_local)
+                            .i32_const(0) // offset into data segment
+                            .i32_const(js_export_len) // size to copy
+                            .memory_init(identifiers.memory, fn_name_data) // copy fn name into allocated memory
+                            .data_drop(fn_name_data)
+                            // Call invoke.
+                            .local_get(bc_metadata.ptr)
+                            .i32_const(bc_metadata.len)
+                            .i32_const(1) // set function name option discriminator to some
+                            .local_get(fn_name_ptr_local)
+                            .i32_const(js_export_len)
+                            .call(identifiers.invoke);
+                        export_fn.finish(vec![], &mut module.funcs)
+                    }
+                };
                 module.exports.add(&export.wit, export_fn);
             }
         }
@@ -424,50 +846,100 @@ impl Generator {
 
     /// Clean-up the generated Wasm.
     fn postprocess(&self, module: &mut Module) -> Result<Vec<u8>> {
-        match self.linking {
-            LinkingKind::Static => {
-                // Remove no longer necessary exports.
-                module.exports.remove("invoke")?;
+        if let LinkingKind::Static = self.linking {
+            // Remove no longer necessary exports.
+            module.exports.remove("invoke")?;
+            if self.static_plugin().capabilities().compile_src {
                 module.exports.remove("compile-src")?;
+            }
+            if self.static_plugin().capabilities().call_export {
+                module.exports.remove("call-export")?;
+            }
+            if self.static_plugin().capabilities().invoke_compressed {
+                module.exports.remove("invoke-compressed")?;
+            }
+            if self.static_plugin().capabilities().configure_module {
+                module.exports.remove("configure-module")?;
+            }
+        }
+        Ok(module.emit_wasm())
+    }
 
-                // Run wasm-opt to optimize.
-                let tempdir = tempfile::tempdir()?;
-                let tempfile_path = tempdir.path().join("temp.wasm");
+    /// Optimize the generated Wasm.
+    fn optimize(&self, wasm: &[u8]) -> Result<Vec<u8>> {
+        match self.linking {
+            LinkingKind::Static => self.optimization.run(wasm, self.debug_info),
+            LinkingKind::Dynamic => Ok(wasm.to_vec()),
+        }
+    }
 
-                module.emit_wasm_file(&tempfile_path)?;
+    /// Generate a Wasm module which will run the provided JS source code.
+    pub async fn generate(&mut self, js: &js::JS) -> Result<Vec<u8>> {
+        let initialized_plugin = self.initialize_plugin().await?;
+        let compiler = Compiler::new(&self.plugin)?;
+        let wasm = self.generate_module(initialized_plugin.as_deref(), &compiler, js)?;
+        let wasm = self.pre_evaluate_module(wasm).await?;
+        self.optimize(&wasm)
+    }
 
-                OptimizationOptions::new_opt_level_3() // Aggressively optimize for speed.
-                    .shrink_level(ShrinkLevel::Level0) // Don't optimize for size at the expense of performance.
-                    .debug_info(false)
-                    .run(&tempfile_path, &tempfile_path)?;
+    /// Generate a Wasm module for each of the provided JS sources.
+    ///
+    /// The plugin is pre-initialized and compiled once and reused for every
+    /// source, and the modules are generated in parallel. The generated
+    /// modules are returned in the same order as the sources.
+    pub async fn generate_all(&self, sources: &[js::JS]) -> Result<Vec<Vec<u8>>> {
+        let initialized_plugin = self.initialize_plugin().await?;
+        let initialized_plugin = initialized_plugin.as_deref();
+        let compiler = &Compiler::new(&self.plugin)?;
+
+        let modules = map_parallel(sources, |js| {
+            self.generate_module(initialized_plugin, compiler, js)
+        })?;
+        // Wizer runs asynchronously so modules are pre-evaluated one at a
+        // time.
+        let mut pre_evaluated = Vec::with_capacity(modules.len());
+        for wasm in modules {
+            pre_evaluated.push(self.pre_evaluate_module(wasm).await?);
+        }
+        map_parallel(&pre_evaluated, |wasm| self.optimize(wasm))
+    }
 
-                Ok(fs::read(&tempfile_path)?)
+    /// Generate an unoptimized Wasm module from an initialized plugin.
+    fn generate_module(
+        &self,
+        initialized_plugin: Option<&[u8]>,
+        compiler: &Compiler,
+        js: &js::JS,
+    ) -> Result<Vec<u8>> {
+        if self.pre_evaluate {
+            if matches!(self.linking, LinkingKind::Dynamic) {
+                bail!("Pre-evaluation is only supported with static linking");
+            }
+            if !self.static_plugin().capabilities().call_export {
+                bail!("Pre-evaluation requires a plugin that exports `call-export`");
             }
-            LinkingKind::Dynamic => Ok(module.emit_wasm()),
         }
-    }
 
-    /// Generate a Wasm module which will run the provided JS source code.
-    pub async fn generate(&mut self, js: &js::JS) -> Result<Vec<u8>> {
-        if self.wit_opts.defined() {
-            self.function_exports = exports::process_exports(
+        let function_exports = if self.wit_opts.defined() {
+            exports::process_exports(
                 js,
                 self.wit_opts.unwrap_path(),
                 self.wit_opts.unwrap_world(),
-            )?;
-        }
+            )?
+        } else {
+            Exports::default()
+        };
 
-        let mut module = self.generate_initial_module().await?;
+        let mut module = self.generate_initial_module(initialized_plugin)?;
         let identifiers = self.resolve_identifiers(&mut module)?;
-        let bc_metadata = self.generate_main(&mut module, js, &identifiers)?;
-        self.generate_exports(&mut module, &identifiers, &bc_metadata)?;
-
-        transform::add_producers_section(
-            &mut module.producers,
-            self.producer_version
-                .as_deref()
-                .unwrap_or(env!("CARGO_PKG_VERSION")),
-        );
+        let bc_metadata = self.generate_main(&mut module, js, &identifiers, compiler)?;
+        self.generate_exports(&mut module, &identifiers, &bc_metadata, &function_exports)?;
+
+        let javy_version = self
+            .producer_version
+            .as_deref()
+            .unwrap_or(env!("CARGO_PKG_VERSION"));
+        transform::add_producers_section(&mut module.producers, javy_version);
         match self.source_embedding {
             SourceEmbedding::Omitted => {}
             SourceEmbedding::Uncompressed => {
@@ -477,8 +949,39 @@ impl Generator {
                 module.customs.add(SourceCodeSection::compressed(js)?);
             }
         }
+        if let Some(version) = self.bytecode_version() {
+            module.customs.add(BytecodeInfo {
+                version,
+                plugin_fingerprint: self.plugin.fingerprint(),
+            });
+        }
+        module
+            .customs
+            .add(self.javy_config(javy_version)?.to_section()?);
 
         let wasm = self.postprocess(&mut module)?;
         Ok(wasm)
     }
 }
+
+/// Apply `f` to each item across a scoped thread per available CPU, returning
+/// the results in the same order as the items.
+fn map_parallel<T, F>(items: &[T], f: F) -> Result<Vec<Vec<u8>>>
+where
+    T: Sync,
+    F: Fn(&T) -> Result<Vec<u8>> + Sync,
+{
+    let f = &f;
+    let workers = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let chunk_size = items.len().div_ceil(workers).max(1);
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("code generation thread to not panic"))
+            .collect()
+    })
+}
@@ -1,19 +1,43 @@
 use anyhow::{Result, anyhow, bail};
+use sha2::{Digest, Sha256};
 use std::{borrow::Cow, fs, path::Path, str};
 use walrus::{ExportItem, ValType};
 use wasmparser::Parser;
 
+/// Optional functionality supported by a plugin.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Capabilities {
+    /// Whether the plugin exports `call-export` to invoke a function on the
+    /// module evaluated by `invoke` without evaluating it again.
+    pub(crate) call_export: bool,
+    /// Whether the plugin exports `compile-src` to compile JS to bytecode.
+    pub(crate) compile_src: bool,
+    /// Whether the plugin exports `invoke-compressed` to evaluate Brotli
+    /// compressed bytecode.
+    pub(crate) invoke_compressed: bool,
+    /// Whether the plugin exports `configure-module` to apply the runtime
+    /// config of dynamically linked modules.
+    pub(crate) configure_module: bool,
+    /// The version of the bytecode format evaluated by the plugin, declared
+    /// in its `javy_bytecode_version` custom section.
+    pub(crate) bytecode_version: Option<u32>,
+}
+
 /// A Javy plugin.
 #[derive(Clone, Debug, Default)]
 pub struct Plugin {
     bytes: Cow<'static, [u8]>,
+    capabilities: Capabilities,
 }
 
 impl Plugin {
     /// Constructs a new [`Plugin`].
     pub fn new(bytes: Cow<'static, [u8]>) -> Result<Self> {
-        Self::validate(&bytes)?;
-        Ok(Self { bytes })
+        let capabilities = Self::validate_capabilities(&bytes)?;
+        Ok(Self {
+            bytes,
+            capabilities,
+        })
     }
 
     /// Constructs a new [`Plugin`] from a given path.
@@ -27,13 +51,36 @@ impl Plugin {
         &self.bytes
     }
 
+    /// Returns the SHA-256 digest of the plugin as a hex string.
+    pub fn fingerprint(&self) -> String {
+        format!("{:x}", Sha256::digest(&self.bytes))
+    }
+
+    /// Returns the optional functionality supported by the plugin.
+    pub(crate) fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
     /// Validates if `plugin_bytes` are a valid plugin.
     pub fn validate(plugin_bytes: &[u8]) -> Result<()> {
+        Self::validate_capabilities(plugin_bytes).map(|_| ())
+    }
+
+    /// Validates if `plugin_bytes` are a valid plugin and returns the optional
+    /// functionality it supports.
+    fn validate_capabilities(plugin_bytes: &[u8]) -> Result<Capabilities> {
         if !Parser::is_core_wasm(plugin_bytes) {
             bail!("Could not process plugin: Expected Wasm module, received unknown file type");
         }
 
         let mut errors = vec![];
+        let invoke_params = [
+            ValType::I32,
+            ValType::I32,
+            ValType::I32,
+            ValType::I32,
+            ValType::I32,
+        ];
 
         let module = walrus::Module::from_buffer(plugin_bytes)?;
 
@@ -44,26 +91,51 @@ impl Plugin {
         if let Err(err) = validate_exported_func(&module, "initialize-runtime", &[], &[]) {
             errors.push(err);
         }
-        if let Err(err) = validate_exported_func(
-            &module,
-            "compile-src",
-            &[ValType::I32, ValType::I32],
-            &[ValType::I32],
-        ) {
+        // `compile-src` is optional so plugins that can only execute bytecode
+        // are supported.
+        let compile_src = module.exports.get_func("compile-src").is_ok();
+        if compile_src
+            && let Err(err) = validate_exported_func(
+                &module,
+                "compile-src",
+                &[ValType::I32, ValType::I32],
+                &[ValType::I32],
+            )
+        {
             errors.push(err);
         }
-        if let Err(err) = validate_exported_func(
-            &module,
-            "invoke",
-            &[
-                ValType::I32,
-                ValType::I32,
-                ValType::I32,
-                ValType::I32,
-                ValType::I32,
-            ],
-            &[],
-        ) {
+        if let Err(err) = validate_exported_func(&module, "invoke", &invoke_params, &[]) {
+            errors.push(err);
+        }
+        // `invoke-compressed` is optional since it's only needed for modules
+        // with compressed bytecode.
+        let invoke_compressed = module.exports.get_func("invoke-compressed").is_ok();
+        if invoke_compressed
+            && let Err(err) =
+                validate_exported_func(&module, "invoke-compressed", &invoke_params, &[])
+        {
+            errors.push(err);
+        }
+        // `configure-module` is optional since it's only needed for
+        // dynamically linked modules with a runtime config.
+        let configure_module = module.exports.get_func("configure-module").is_ok();
+        if configure_module
+            && let Err(err) = validate_exported_func(
+                &module,
+                "configure-module",
+                &[ValType::I32, ValType::I32],
+                &[],
+            )
+        {
+            errors.push(err);
+        }
+        // `call-export` is optional so plugins built before it was introduced
+        // remain supported.
+        let call_export = module.exports.get_func("call-export").is_ok();
+        if call_export
+            && let Err(err) =
+                validate_exported_func(&module, "call-export", &[ValType::I32, ValType::I32], &[])
+        {
             errors.push(err);
         }
 
@@ -83,10 +155,36 @@ impl Plugin {
             errors.push("missing custom section named `import_namespace`".to_string());
         }
 
+        // The bytecode version is optional so plugins built before it was
+        // introduced remain supported.
+        let bytecode_version = module
+            .customs
+            .iter()
+            .find(|(_, section)| section.name() == "javy_bytecode_version")
+            .and_then(|(_, section)| {
+                let data = section.data(&Default::default());
+                match <[u8; 4]>::try_from(data.as_ref()) {
+                    Ok(version) => Some(u32::from_le_bytes(version)),
+                    Err(_) => {
+                        errors.push(
+                            "custom section `javy_bytecode_version` must contain a 32-bit integer"
+                                .to_string(),
+                        );
+                        None
+                    }
+                }
+            });
+
         if !errors.is_empty() {
             bail!("Could not process plugin: {}", errors.join(", "))
         }
-        Ok(())
+        Ok(Capabilities {
+            call_export,
+            compile_src,
+            invoke_compressed,
+            configure_module,
+            bytecode_version,
+        })
     }
 
     pub(crate) fn import_namespace(&self) -> Result<String> {
@@ -185,10 +283,9 @@ mod tests {
         assert_eq!(
             error.to_string(),
             "Could not process plugin: missing export for function named \
-            `initialize-runtime`, missing export for function named \
-            `compile-src`, type for function `invoke` is incorrect, missing \
-            exported memory named `memory`, missing custom section named \
-            `import_namespace`"
+            `initialize-runtime`, type for function `invoke` is incorrect, \
+            missing exported memory named `memory`, missing custom section \
+            named `import_namespace`"
         );
         Ok(())
     }
@@ -202,9 +299,8 @@ mod tests {
             error.to_string(),
             "Could not process plugin: missing export for function named \
             `initialize-runtime`, missing export for function named \
-            `compile-src`, missing export for function named `invoke`, \
-            missing exported memory named `memory`, missing custom section \
-            named `import_namespace`"
+            `invoke`, missing exported memory named `memory`, missing custom \
+            section named `import_namespace`"
         );
         Ok(())
     }
@@ -247,4 +343,76 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_validate_plugin_with_wrong_params_for_compile_src() -> Result<()> {
+        let mut module = walrus::Module::with_config(ModuleConfig::default());
+        let compile_src = FunctionBuilder::new(&mut module.types, &[ValType::I32], &[])
+            .finish(vec![], &mut module.funcs);
+        module.exports.add("compile-src", compile_src);
+
+        let plugin_bytes = module.emit_wasm();
+        let error = Plugin::new(plugin_bytes.into()).err().unwrap();
+        let expected_part_of_error = "type for function `compile-src` is incorrect";
+        if !error.to_string().contains(expected_part_of_error) {
+            panic!(
+                "Expected error to contain '{expected_part_of_error}' but it did not. Full error is: '{error}'"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_plugin_with_wrong_params_for_invoke_compressed() -> Result<()> {
+        let mut module = walrus::Module::with_config(ModuleConfig::default());
+        let invoke_compressed = FunctionBuilder::new(&mut module.types, &[ValType::I32], &[])
+            .finish(vec![], &mut module.funcs);
+        module.exports.add("invoke-compressed", invoke_compressed);
+
+        let plugin_bytes = module.emit_wasm();
+        let error = Plugin::new(plugin_bytes.into()).err().unwrap();
+        let expected_part_of_error = "type for function `invoke-compressed` is incorrect";
+        if !error.to_string().contains(expected_part_of_error) {
+            panic!(
+                "Expected error to contain '{expected_part_of_error}' but it did not. Full error is: '{error}'"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_plugin_with_wrong_params_for_call_export() -> Result<()> {
+        let mut module = walrus::Module::with_config(ModuleConfig::default());
+        let call_export = FunctionBuilder::new(&mut module.types, &[ValType::I32], &[])
+            .finish(vec![], &mut module.funcs);
+        module.exports.add("call-export", call_export);
+
+        let plugin_bytes = module.emit_wasm();
+        let error = Plugin::new(plugin_bytes.into()).err().unwrap();
+        let expected_part_of_error = "type for function `call-export` is incorrect";
+        if !error.to_string().contains(expected_part_of_error) {
+            panic!(
+                "Expected error to contain '{expected_part_of_error}' but it did not. Full error is: '{error}'"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_plugin_with_wrong_params_for_configure_module() -> Result<()> {
+        let mut module = walrus::Module::with_config(ModuleConfig::default());
+        let configure_module = FunctionBuilder::new(&mut module.types, &[ValType::I32], &[])
+            .finish(vec![], &mut module.funcs);
+        module.exports.add("configure-module", configure_module);
+
+        let plugin_bytes = module.emit_wasm();
+        let error = Plugin::new(plugin_bytes.into()).err().unwrap();
+        let expected_part_of_error = "type for function `configure-module` is incorrect";
+        if !error.to_string().contains(expected_part_of_error) {
+            panic!(
+                "Expected error to contain '{expected_part_of_error}' but it did not. Full error is: '{error}'"
+            );
+        }
+        Ok(())
+    }
 }
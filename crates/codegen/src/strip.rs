@@ -0,0 +1,188 @@
+//! Removal of engine code a statically linked module can't call.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use walrus::{
+    ConstExpr, DataKind, ElementItems, ElementKind, ExportItem, FunctionBuilder, FunctionId,
+    FunctionKind, GlobalKind, ImportKind,
+    ir::{Const, Value, Visitor, dfs_in_order},
+};
+use wasmparser::{Parser, Payload};
+
+use crate::transform;
+
+/// A statically linked module with unused code removed.
+#[derive(Debug)]
+pub struct StrippedModule {
+    /// The stripped module.
+    pub wasm: Vec<u8>,
+    /// The number of functions removed from the module.
+    pub removed_functions: usize,
+}
+
+/// Removes the functions a statically linked module generated by Javy can't
+/// call.
+///
+/// Functions called through the function table, such as the JS compiler or
+/// the implementations of builtins, are only kept if their table index is
+/// referenced by reachable code or found in the module's data, which includes
+/// the state of the engine snapshotted when the plugin was pre-initialized.
+/// So the code of intrinsics disabled in the JS runtime config, and of the
+/// compiler when `eval` is disabled, is removed. Function pointers in memory
+/// are assumed to be 4-byte aligned.
+///
+/// Modules which import or export their function table are returned unchanged
+/// since the host may call any function in the table. The `name` custom
+/// section is kept if the module has one.
+pub fn strip_unused(wasm: &[u8]) -> Result<StrippedModule> {
+    let mut config = transform::module_config();
+    config.generate_name_section(has_name_section(wasm)?);
+    let mut module = config.parse(wasm)?;
+    let unchanged = || StrippedModule {
+        wasm: wasm.to_vec(),
+        removed_functions: 0,
+    };
+
+    let shared_table = module
+        .imports
+        .iter()
+        .any(|import| matches!(import.kind, ImportKind::Table(_)))
+        || module
+            .exports
+            .iter()
+            .any(|export| matches!(export.item, ExportItem::Table(_)));
+    if shared_table {
+        return Ok(unchanged());
+    }
+
+    // Map each table index to the function in that slot.
+    let mut slots = HashMap::new();
+    let mut roots = vec![];
+    for element in module.elements.iter() {
+        match (&element.kind, &element.items) {
+            (
+                ElementKind::Active {
+                    offset: ConstExpr::Value(Value::I32(offset)),
+                    ..
+                },
+                ElementItems::Functions(funcs),
+            ) => {
+                for (index, func) in (*offset as u32..).zip(funcs) {
+                    slots.insert(index, *func);
+                }
+            }
+            (ElementKind::Active { .. }, _) => return Ok(unchanged()),
+            (_, ElementItems::Functions(funcs)) => roots.extend(funcs),
+            (_, ElementItems::Expressions(..)) => return Ok(unchanged()),
+        }
+    }
+
+    roots.extend(
+        module
+            .exports
+            .iter()
+            .filter_map(|export| match export.item {
+                ExportItem::Function(func) => Some(func),
+                _ => None,
+            }),
+    );
+    roots.extend(module.start);
+    for global in module.globals.iter() {
+        match global.kind {
+            GlobalKind::Local(ConstExpr::Value(Value::I32(value))) => {
+                roots.extend(slots.get(&(value as u32)));
+            }
+            GlobalKind::Local(ConstExpr::RefFunc(func)) => roots.push(func),
+            _ => {}
+        }
+    }
+    for data in module.data.iter() {
+        // Only aligned pointers are considered in memory initialized by
+        // active segments, but the destination of passive segments isn't
+        // known.
+        let (start, step) = match data.kind {
+            DataKind::Active {
+                offset: ConstExpr::Value(Value::I32(offset)),
+                ..
+            } => ((4 - offset.rem_euclid(4)) as usize % 4, 4),
+            _ => (0, 1),
+        };
+        let values = data.value.get(start..).unwrap_or_default();
+        for value in values.windows(4).step_by(step) {
+            let value = u32::from_le_bytes(value.try_into().unwrap());
+            roots.extend(slots.get(&value));
+        }
+    }
+
+    let mut live = HashSet::new();
+    while let Some(func) = roots.pop() {
+        if !live.insert(func) {
+            continue;
+        }
+        if let FunctionKind::Local(local) = &module.funcs.get(func).kind {
+            let mut references = References::default();
+            dfs_in_order(&mut references, local, local.entry_block());
+            roots.extend(references.functions);
+            roots.extend(
+                references
+                    .constants
+                    .iter()
+                    .filter_map(|value| slots.get(value)),
+            );
+        }
+    }
+
+    // Point slots of unreachable functions at a function that traps so the
+    // functions can be removed. The removed functions are counted net of the
+    // trap.
+    let functions = module.funcs.iter().count();
+    if slots.values().any(|func| !live.contains(func)) {
+        let mut trap = FunctionBuilder::new(&mut module.types, &[], &[]);
+        trap.func_body().unreachable();
+        let trap = trap.finish(vec![], &mut module.funcs);
+        for element in module.elements.iter_mut() {
+            if let ElementItems::Functions(funcs) = &mut element.items {
+                for func in funcs.iter_mut().filter(|func| !live.contains(*func)) {
+                    *func = trap;
+                }
+            }
+        }
+    }
+
+    walrus::passes::gc::run(&mut module);
+    Ok(StrippedModule {
+        wasm: module.emit_wasm(),
+        removed_functions: functions - module.funcs.iter().count(),
+    })
+}
+
+fn has_name_section(wasm: &[u8]) -> Result<bool> {
+    for payload in Parser::new(0).parse_all(wasm) {
+        if let Payload::CustomSection(section) = payload?
+            && section.name() == "name"
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// The functions and `i32` constants referenced by a function's body.
+#[derive(Default)]
+struct References {
+    functions: Vec<FunctionId>,
+    constants: Vec<u32>,
+}
+
+impl<'instr> Visitor<'instr> for References {
+    fn visit_function_id(&mut self, function: &FunctionId) {
+        self.functions.push(*function);
+    }
+
+    fn visit_const(&mut self, instr: &Const) {
+        if let Value::I32(value) = instr.value {
+            self.constants.push(value as u32);
+        }
+    }
+}
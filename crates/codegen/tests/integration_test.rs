@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
-use javy_codegen::{Generator, JS, LinkingKind, Plugin, WitOptions};
+use javy_codegen::{Generator, JS, LinkingKind, Optimization, Plugin, WitOptions, strip_unused};
 
 #[tokio::test]
 async fn test_empty() -> Result<()> {
@@ -30,7 +30,9 @@ async fn test_empty() -> Result<()> {
 async fn test_snapshot_for_dynamically_linked_module() -> Result<()> {
     let sample_scripts = sample_scripts_dir();
     let js = JS::from_file(&sample_scripts.join("exported-functions.js"))?;
-    let wasm = Generator::new(default_plugin()?)
+    let plugin = default_plugin()?;
+    let fingerprint = plugin.fingerprint();
+    let wasm = Generator::new(plugin)
         .linking(LinkingKind::Dynamic)
         .wit_opts(WitOptions::from_tuple((
             Some(sample_scripts.join("exported-functions.wit")),
@@ -39,7 +41,8 @@ async fn test_snapshot_for_dynamically_linked_module() -> Result<()> {
         .producer_version("snapshot".into())
         .generate(&js)
         .await?;
-    let wat = wasmprinter::print_bytes(wasm)?;
+    // The fingerprint changes whenever the plugin is rebuilt.
+    let wat = wasmprinter::print_bytes(wasm)?.replace(&fingerprint, "<fingerprint>");
     insta::assert_snapshot!("default_dynamic", wat);
     Ok(())
 }
@@ -75,6 +78,224 @@ async fn test_deterministic_builds_produce_identical_output() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_generate_all_matches_individual_builds() -> Result<()> {
+    let sample_scripts = sample_scripts_dir();
+    let sources = [
+        JS::from_file(&sample_scripts.join("empty.js"))?,
+        JS::from_file(&sample_scripts.join("exported-functions.js"))?,
+    ];
+
+    let mut generator = Generator::new(default_plugin()?);
+    generator.linking(LinkingKind::Static).deterministic(true);
+
+    let all = generator.generate_all(&sources).await?;
+    assert_eq!(sources.len(), all.len());
+    for (js, wasm) in sources.iter().zip(all) {
+        assert_eq!(generator.generate(js).await?, wasm);
+    }
+
+    assert!(generator.generate_all(&[]).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_plugin_produces_identical_output() -> Result<()> {
+    let js = JS::from_file(&sample_scripts_dir().join("empty.js"))?;
+    let cache_dir = tempfile::tempdir()?;
+
+    let generate = |cache: bool| {
+        let (js, cache_dir) = (&js, cache_dir.path());
+        async move {
+            let mut generator = Generator::new(default_plugin()?);
+            generator.linking(LinkingKind::Static).deterministic(true);
+            if cache {
+                generator.cache_dir(cache_dir.to_path_buf());
+            }
+            generator.generate(js).await
+        }
+    };
+
+    let uncached = generate(false).await?;
+    let populated = generate(true).await?;
+    let cached = generate(true).await?;
+    assert_eq!(uncached, populated);
+    assert_eq!(populated, cached);
+    assert_eq!(1, std::fs::read_dir(cache_dir.path())?.count());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_optimizing_for_size_produces_smaller_module() -> Result<()> {
+    let js = JS::from_file(&sample_scripts_dir().join("empty.js"))?;
+    let generate = |optimization: Optimization| {
+        let js = &js;
+        async move {
+            let mut generator = Generator::new(default_plugin()?);
+            generator
+                .linking(LinkingKind::Static)
+                .optimization(optimization);
+            generator.generate(js).await
+        }
+    };
+
+    let unoptimized = generate(Optimization::None).await?;
+    let speed = generate(Optimization::Speed).await?;
+    let size = generate(Optimization::AggressiveSize).await?;
+    let custom = generate(Optimization::Custom(vec!["dce".into()])).await?;
+    assert!(size.len() < unoptimized.len());
+    assert!(size.len() <= speed.len());
+    assert!(custom.len() <= unoptimized.len());
+    walrus::Module::from_buffer(&size)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_strip_unused() -> Result<()> {
+    let js = JS::from_file(&sample_scripts_dir().join("empty.js"))?;
+    let mut generator = Generator::new(default_plugin()?);
+    generator.linking(LinkingKind::Static);
+    let wasm = generator.generate(&js).await?;
+
+    let stripped = strip_unused(&wasm)?;
+    let functions = walrus::Module::from_buffer(&wasm)?.funcs.iter().count();
+    let module = walrus::Module::from_buffer(&stripped.wasm)?;
+    assert_eq!(
+        functions - stripped.removed_functions,
+        module.funcs.iter().count()
+    );
+    assert!(module.exports.get_func("_start").is_ok());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_debug_info() -> Result<()> {
+    let js = JS::from_file(&sample_scripts_dir().join("empty.js"))?;
+    let generate = |debug_info: bool| {
+        let js = &js;
+        async move {
+            let mut generator = Generator::new(default_plugin()?);
+            generator
+                .linking(LinkingKind::Static)
+                .debug_info(debug_info);
+            generator.generate(js).await
+        }
+    };
+
+    let without_names = generate(false).await?;
+    assert!(!has_name_section(&without_names)?);
+    let with_names = generate(true).await?;
+    assert!(has_name_section(&with_names)?);
+    assert!(has_name_section(&strip_unused(&with_names)?.wasm)?);
+    let module = walrus::Module::from_buffer(&with_names)?;
+    assert!(module.funcs.iter().any(|func| func.name.is_some()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_compress_bytecode() -> Result<()> {
+    let js = JS::from_file(&sample_scripts_dir().join("exported-functions.js"))?;
+    let mut generator = Generator::new(default_plugin()?);
+    generator
+        .linking(LinkingKind::Dynamic)
+        .compress_bytecode(true);
+    let wasm = generator.generate(&js).await?;
+
+    let module = walrus::Module::from_buffer(&wasm)?;
+    assert!(
+        module
+            .imports
+            .iter()
+            .any(|import| import.name == "invoke-compressed")
+    );
+    assert!(module.imports.iter().all(|import| import.name != "invoke"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_static_module_removes_plugin_exports() -> Result<()> {
+    let js = JS::from_file(&sample_scripts_dir().join("empty.js"))?;
+    let mut generator = Generator::new(default_plugin()?);
+    generator.linking(LinkingKind::Static);
+    let wasm = generator.generate(&js).await?;
+
+    let module = walrus::Module::from_buffer(&wasm)?;
+    for export in ["invoke", "compile-src", "call-export", "configure-module"] {
+        assert!(
+            module.exports.iter().all(|e| e.name != export),
+            "{export} should be removed"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "plugin_internal")]
+#[tokio::test]
+async fn test_dynamically_linked_module_is_configured_once() -> Result<()> {
+    use wasmtime::{Caller, Engine, Linker, Memory, MemoryType, Module, Store};
+
+    let sample_scripts = sample_scripts_dir();
+    let js = JS::from_file(&sample_scripts.join("exported-functions.js"))?;
+    let wasm = Generator::new(default_plugin()?)
+        .linking(LinkingKind::Dynamic)
+        .wit_opts(WitOptions::from_tuple((
+            Some(sample_scripts.join("exported-functions.wit")),
+            Some("exported-logs".into()),
+        ))?)
+        .js_runtime_config(br#"{"event-loop":true}"#.to_vec())
+        .generate(&js)
+        .await?;
+
+    // Stub the plugin, counting the calls to `configure-module`.
+    let engine = Engine::default();
+    let module = Module::new(&engine, &wasm)?;
+    let namespace = module
+        .imports()
+        .find(|import| import.name() == "memory")
+        .expect("memory import")
+        .module()
+        .to_string();
+    let mut store = Store::new(&engine, 0);
+    let mut linker = Linker::new(&engine);
+    let memory = Memory::new(&mut store, MemoryType::new(1, None))?;
+    linker.define(&store, &namespace, "memory", memory)?;
+    linker.func_wrap(
+        &namespace,
+        "cabi_realloc",
+        |_: i32, _: i32, _: i32, _: i32| 0,
+    )?;
+    linker.func_wrap(
+        &namespace,
+        "invoke",
+        |_: i32, _: i32, _: i32, _: i32, _: i32| {},
+    )?;
+    linker.func_wrap(
+        &namespace,
+        "configure-module",
+        |mut caller: Caller<'_, u32>, _: i32, _: i32| *caller.data_mut() += 1,
+    )?;
+    let instance = linker.instantiate(&mut store, &module)?;
+    for export in ["_start", "log"] {
+        instance
+            .get_typed_func::<(), ()>(&mut store, export)?
+            .call(&mut store, ())?;
+    }
+    assert_eq!(1, *store.data());
+    Ok(())
+}
+
+fn has_name_section(wasm: &[u8]) -> Result<bool> {
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let wasmparser::Payload::CustomSection(section) = payload?
+            && section.name() == "name"
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
 fn cargo_manifest_dir() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
 }
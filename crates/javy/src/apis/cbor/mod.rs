@@ -0,0 +1,66 @@
+use anyhow::{Result, anyhow};
+
+use crate::{
+    Args, cbor, hold, hold_and_release,
+    quickjs::{ArrayBuffer, Ctx, Function, Object, TypedArray, Value},
+    to_js_error,
+};
+
+/// Register the `Javy.CBOR.encode` and `Javy.CBOR.decode` functions on the
+/// global object.
+pub(crate) fn register(this: Ctx<'_>) -> Result<()> {
+    let globals = this.globals();
+    if globals.get::<_, Object>("Javy").is_err() {
+        globals.set("Javy", Object::new(this.clone())?)?
+    }
+
+    let javy: Object = globals.get("Javy")?;
+    let cbor = Object::new(this.clone())?;
+
+    cbor.set(
+        "encode",
+        Function::new(this.clone(), |cx, args| {
+            let (cx, args) = hold_and_release!(cx, args);
+            encode(hold!(cx.clone(), args)).map_err(|e| to_js_error(cx, e))
+        }),
+    )?;
+
+    cbor.set(
+        "decode",
+        Function::new(this.clone(), |cx, args| {
+            let (cx, args) = hold_and_release!(cx, args);
+            decode(hold!(cx.clone(), args)).map_err(|e| to_js_error(cx, e))
+        }),
+    )?;
+
+    javy.set("CBOR", cbor)?;
+    Ok(())
+}
+
+/// Encodes the value as CBOR and returns the bytes in a `Uint8Array`.
+fn encode(args: Args<'_>) -> Result<Value<'_>> {
+    let (cx, args) = args.release();
+    let value = args
+        .first()
+        .cloned()
+        .ok_or_else(|| anyhow!("Javy.CBOR.encode expects 1 parameter: the value to encode"))?;
+    let bytes = cbor::transcode_output(value)?;
+    Ok(TypedArray::<u8>::new(cx, bytes)?.into_value())
+}
+
+/// Decodes the CBOR encoded bytes of an `ArrayBuffer` or `Uint8Array`.
+fn decode(args: Args<'_>) -> Result<Value<'_>> {
+    let (cx, args) = args.release();
+    let value = args.first().cloned().ok_or_else(|| {
+        anyhow!("Javy.CBOR.decode expects 1 parameter: an ArrayBuffer or Uint8Array")
+    })?;
+    let bytes = if let Ok(buffer) = value.get::<ArrayBuffer>() {
+        buffer.as_bytes().map(<[u8]>::to_vec)
+    } else if let Ok(array) = value.get::<TypedArray<u8>>() {
+        array.as_bytes().map(<[u8]>::to_vec)
+    } else {
+        None
+    }
+    .ok_or_else(|| anyhow!("Data needs to be an ArrayBuffer or Uint8Array"))?;
+    cbor::transcode_input(cx, &bytes)
+}
@@ -0,0 +1,177 @@
+use anyhow::{Error, Result};
+
+use crate::quickjs::{
+    self, Ctx, Function, IntoJs, Object, Value,
+    function::{Constructor, Func, Rest, This},
+    object::Property,
+};
+
+/// Replace `Date` and `performance` on the global object with implementations
+/// that always report `fixed_time`, in milliseconds since the Unix epoch.
+///
+/// The replacements are installed without evaluating JavaScript, so this works
+/// when the `Eval` intrinsic is disabled.
+///
+/// Must be registered after the `Date` and `Performance` intrinsics.
+pub(crate) fn register(this: Ctx<'_>, fixed_time: f64) -> Result<()> {
+    let globals = this.globals();
+    if let Some(native) = globals.get::<_, Value>("Date")?.into_constructor() {
+        register_date(&this, native, fixed_time)?;
+    }
+    if globals.get::<_, Value>("performance")?.is_object() {
+        let performance = Object::new(this.clone())?;
+        performance.prop(
+            "now",
+            Property::from(Func::from(|| 0.0))
+                .writable()
+                .enumerable()
+                .configurable(),
+        )?;
+        performance.prop("timeOrigin", Property::from(fixed_time).enumerable())?;
+        globals.set("performance", performance)?;
+    }
+    Ok::<_, Error>(())
+}
+
+/// Replace `Date` with a constructor creating dates at `fixed_time` when no
+/// arguments are given, and delegating to the `native` constructor otherwise.
+fn register_date<'js>(this: &Ctx<'js>, native: Constructor<'js>, fixed_time: f64) -> Result<()> {
+    let construct: Function = this
+        .globals()
+        .get::<_, Object>("Reflect")?
+        .get("construct")?;
+    let native_date = native.clone();
+    let date = Function::new(
+        this.clone(),
+        move |cx: Ctx<'js>,
+              new_target: This<Value<'js>>,
+              args: Rest<Value<'js>>|
+              -> quickjs::Result<Value<'js>> {
+            // When called as a constructor, `this` is `new.target`.
+            if !new_target.0.is_constructor() {
+                let date: Object = native_date.construct((fixed_time,))?;
+                let to_string: Function = date.get("toString")?;
+                return to_string.call((This(date),));
+            }
+            let args = if args.0.is_empty() {
+                vec![fixed_time.into_js(&cx)?]
+            } else {
+                args.0
+            };
+            construct.call((native_date.clone(), args, new_target.0))
+        },
+    )?
+    .with_constructor(true)
+    .with_name("Date")?
+    .with_length(native.get("length")?)?;
+
+    let prototype: Object = native.get("prototype")?;
+    date.prop("prototype", Property::from(prototype.clone()))?;
+    date.prop(
+        "now",
+        Property::from(Func::from(move || fixed_time))
+            .writable()
+            .configurable(),
+    )?;
+    for name in ["parse", "UTC"] {
+        date.prop(
+            name,
+            Property::from(native.get::<_, Value>(name)?)
+                .writable()
+                .configurable(),
+        )?;
+    }
+    prototype.prop(
+        "constructor",
+        Property::from(date.clone()).writable().configurable(),
+    )?;
+    this.globals().set("Date", date)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Config, Runtime,
+        quickjs::{
+            Function, Object,
+            function::{Constructor, This},
+        },
+    };
+    use anyhow::{Error, Result};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn runtime() -> Result<Runtime> {
+        let mut config = Config::default();
+        config
+            .performance(true)
+            .deterministic(0, UNIX_EPOCH + Duration::from_millis(1_700_000_000_000));
+        Runtime::new(config)
+    }
+
+    #[test]
+    fn test_date_is_fixed() -> Result<()> {
+        let runtime = runtime()?;
+        runtime.context().with(|this| {
+            assert_eq!(1_700_000_000_000.0, this.eval::<f64, _>("Date.now()")?);
+            assert_eq!(
+                1_700_000_000_000.0,
+                this.eval::<f64, _>("new Date().getTime()")?
+            );
+            assert_eq!(
+                this.eval::<String, _>("new Date(1700000000000).toString()")?,
+                this.eval::<String, _>("Date()")?
+            );
+            assert_eq!(0.0, this.eval::<f64, _>("new Date(0).getTime()")?);
+            assert_eq!(
+                86_400_000.0,
+                this.eval::<f64, _>("new Date(Date.UTC(1970, 0, 2)).getTime()")?
+            );
+            assert!(this.eval::<bool, _>("new Date() instanceof Date")?);
+            assert!(this.eval::<bool, _>("new Date().constructor === Date")?);
+            assert!(this.eval::<bool, _>("Date.name === 'Date' && Date.length === 7")?);
+            assert!(
+                this.eval::<bool, _>("class Later extends Date {}; new Later() instanceof Later")?
+            );
+            Ok::<_, Error>(())
+        })
+    }
+
+    #[test]
+    fn test_performance_is_fixed() -> Result<()> {
+        let runtime = runtime()?;
+        runtime.context().with(|this| {
+            assert_eq!(0.0, this.eval::<f64, _>("performance.now()")?);
+            assert_eq!(
+                1_700_000_000_000.0,
+                this.eval::<f64, _>("performance.timeOrigin")?
+            );
+            Ok::<_, Error>(())
+        })
+    }
+
+    #[test]
+    fn test_clock_without_eval() -> Result<()> {
+        let mut config = Config::default();
+        config
+            .eval(false)
+            .performance(true)
+            .deterministic(0, UNIX_EPOCH + Duration::from_millis(1_700_000_000_000));
+        let runtime = Runtime::new(config)?;
+        runtime.context().with(|this| {
+            let date: Constructor = this.globals().get("Date")?;
+            let now: Function = date.get("now")?;
+            assert_eq!(1_700_000_000_000.0, now.call::<_, f64>(())?);
+            let today: Object = date.construct(())?;
+            let get_time: Function = today.get("getTime")?;
+            assert_eq!(
+                1_700_000_000_000.0,
+                get_time.call::<_, f64>((This(today),))?
+            );
+            let performance: Object = this.globals().get("performance")?;
+            let now: Function = performance.get("now")?;
+            assert_eq!(0.0, now.call::<_, f64>(())?);
+            Ok::<_, Error>(())
+        })
+    }
+}
@@ -0,0 +1,144 @@
+use std::{cell::Cell, rc::Rc};
+
+use anyhow::{Result, anyhow, bail};
+
+use crate::{
+    Args, hold, hold_and_release,
+    quickjs::{Ctx, Exception, Function, Object, Value, object::Accessor, qjs},
+    to_js_error,
+};
+
+/// Exit state shared between the `Javy` namespace and the
+/// [`Runtime`](crate::Runtime).
+#[derive(Debug, Default)]
+pub(crate) struct ExitState {
+    /// The exit code assigned to `Javy.exitCode` or passed to `Javy.exit`.
+    pub(crate) code: Cell<Option<i32>>,
+    /// Whether `Javy.exit` was called.
+    pub(crate) requested: Cell<bool>,
+}
+
+/// Register the `Javy.exit` function and the `Javy.exitCode` property on the
+/// global object.
+pub(crate) fn register<'js>(this: Ctx<'js>, state: Rc<ExitState>) -> Result<()> {
+    let globals = this.globals();
+    if globals.get::<_, Object>("Javy").is_err() {
+        globals.set("Javy", Object::new(this.clone())?)?
+    }
+
+    let javy: Object = globals.get("Javy")?;
+
+    let exit_state = state.clone();
+    javy.set(
+        "exit",
+        Function::new(this.clone(), move |cx, args| {
+            let (cx, args) = hold_and_release!(cx, args);
+            exit(hold!(cx.clone(), args), &exit_state).map_err(|e| to_js_error(cx, e))
+        }),
+    )?;
+
+    let get_state = state.clone();
+    javy.prop(
+        "exitCode",
+        Accessor::new(
+            move || get_state.code.get(),
+            move |cx: Ctx<'js>, code: Value<'js>| {
+                let code = if code.is_undefined() || code.is_null() {
+                    None
+                } else {
+                    Some(to_exit_code(&code).map_err(|e| to_js_error(cx, e))?)
+                };
+                state.code.set(code);
+                Ok::<_, crate::quickjs::Error>(())
+            },
+        ),
+    )?;
+
+    Ok(())
+}
+
+fn exit<'js>(args: Args<'js>, state: &ExitState) -> Result<Value<'js>> {
+    let (cx, args) = args.release();
+    // Mirror Node's `process.exit()`, which falls back to `process.exitCode`
+    // when no code is given.
+    if let Some(code) = args.first().filter(|code| !code.is_undefined()) {
+        state.code.set(Some(to_exit_code(code)?));
+    }
+    state.requested.set(true);
+
+    // Throw an uncatchable error to unwind the stack without giving
+    // JavaScript a chance to intercept the exit through `try`/`catch`.
+    let exception = Exception::from_message(cx.clone(), "exit")?;
+    unsafe { qjs::JS_SetUncatchableError(cx.as_raw().as_ptr(), exception.as_raw()) };
+    bail!(cx.throw(exception.into_value()))
+}
+
+fn to_exit_code(value: &Value<'_>) -> Result<i32> {
+    if let Some(code) = value.as_int() {
+        return Ok(code);
+    }
+
+    let code = value
+        .as_number()
+        .ok_or_else(|| anyhow!("Exit code must be a number"))?;
+    if code.fract() != 0.0 || code < i32::MIN as f64 || code > i32::MAX as f64 {
+        bail!("Exit code must be an integer");
+    }
+    Ok(code as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Config, Runtime, quickjs::Value};
+    use anyhow::{Error, Result};
+
+    #[test]
+    fn test_exit_is_not_catchable() -> Result<()> {
+        let mut config = Config::default();
+        config.javy_exit(true);
+        let runtime = Runtime::new(config)?;
+
+        runtime.context().with(|this| {
+            let result = this.eval::<Value<'_>, _>(
+                r#"
+                globalThis.caught = false;
+                try {
+                    Javy.exit(3);
+                } catch (e) {
+                    globalThis.caught = true;
+                }
+            "#,
+            );
+            assert!(result.is_err());
+            let _ = this.catch();
+
+            let caught: bool = this.globals().get("caught")?;
+            assert!(!caught);
+            Ok::<_, Error>(())
+        })?;
+
+        assert!(runtime.exit_requested());
+        assert_eq!(Some(3), runtime.exit_code());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exit_code_property() -> Result<()> {
+        let mut config = Config::default();
+        config.javy_exit(true);
+        let runtime = Runtime::new(config)?;
+
+        assert_eq!(None, runtime.exit_code());
+        runtime.context().with(|this| {
+            this.eval::<(), _>("Javy.exitCode = 4;")?;
+            let code: i32 = this.eval("Javy.exitCode")?;
+            assert_eq!(4, code);
+            assert!(this.eval::<(), _>("Javy.exitCode = 'foo';").is_err());
+            Ok::<_, Error>(())
+        })?;
+
+        assert!(!runtime.exit_requested());
+        assert_eq!(Some(4), runtime.exit_code());
+        Ok(())
+    }
+}
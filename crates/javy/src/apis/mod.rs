@@ -44,7 +44,15 @@
 //! the RNG on first call to `Math.random`. This is helpful to enable when using
 //! using a tool like Wizer to snapshot a [`Runtime`] so that the output of
 //! `Math.random` relies on the WASI context used at runtime and not the WASI
-//! context used when snapshotting.
+//! context used when snapshotting. When
+//! [`Config::deterministic`](crate::Config::deterministic) is set, the RNG is
+//! seeded with the given seed instead.
+//!
+//! ### `Clock`
+//!
+//! Replaces `Date` and `performance` with implementations that always report
+//! a fixed time. Only enabled through
+//! [`Config::deterministic`](crate::Config::deterministic).
 //!
 //! ### `StreamIO`
 //!
@@ -56,12 +64,33 @@
 //! Javy CLI. See [the extending Javy docs](/docs/docs-using-extending.md) for
 //! more details on using a WASI preview 1 plugin.
 //!
+//! ### `Exit`
+//!
+//! Provides an implementation of `Javy.exit` and support for reporting an exit
+//! code through `Javy.exitCode`. `Javy.exit` terminates execution with an error
+//! that can't be caught from JavaScript; the embedder is responsible for
+//! terminating the instance with the code returned by
+//! [`Runtime::exit_code`](crate::Runtime::exit_code).
+//! Disabled by default.
+//!
 //! ###  `JSON`
 //! Provides an efficient implementation of JSON functions based on [`simd-json`](https://crates.io/crates/simd-json/0.13.10)
 //! and [`serde_json`](https://crates.io/crates/serde_json)
 //!
 //! Disabled by default.
+//!
+//! ### `CBOR`
+//!
+//! Provides `Javy.CBOR.encode`, which encodes a value as CBOR in a
+//! `Uint8Array`, and `Javy.CBOR.decode`, which decodes the CBOR in an
+//! `ArrayBuffer` or `Uint8Array`. Requires the `cbor` crate feature.
+//!
+//! Disabled by default.
+#[cfg(feature = "cbor")]
+pub(crate) mod cbor;
+pub(crate) mod clock;
 pub(crate) mod console;
+pub(crate) mod exit;
 #[cfg(feature = "json")]
 pub(crate) mod json;
 pub(crate) mod random;
@@ -1,23 +1,95 @@
-use crate::quickjs::{Ctx, Object, prelude::Func};
-use anyhow::{Error, Result};
+use std::{cell::RefCell, rc::Rc, slice};
+
+use crate::{
+    Args, hold, hold_and_release,
+    quickjs::{
+        Ctx, Function, Object, Value,
+        prelude::{Func, MutFn},
+    },
+    to_js_error,
+};
+use anyhow::{Error, Result, anyhow, bail};
+
+/// The maximum number of bytes `crypto.getRandomValues` fills in one call.
+const MAX_RANDOM_VALUES_BYTES: usize = 65_536;
 
 /// Register a `random` object on the global object that seeds itself at first
-/// execution.
-pub(crate) fn register(cx: Ctx) -> Result<()> {
+/// execution, or with `seed` if one is given.
+///
+/// With a `seed`, `crypto.getRandomValues` is also registered and draws from
+/// the same generator as `Math.random`, so its output is reproducible. It's
+/// not cryptographically secure. Without a `seed`, `crypto` isn't defined.
+pub(crate) fn register(cx: Ctx, seed: Option<u64>) -> Result<()> {
     let globals = cx.globals();
     let math: Object<'_> = globals.get("Math").expect("Math global to be defined");
-    math.set("random", Func::from(fastrand::f64))?;
+    match seed {
+        Some(seed) => {
+            let rng = Rc::new(RefCell::new(fastrand::Rng::with_seed(seed)));
+            let random = rng.clone();
+            math.set(
+                "random",
+                Func::from(MutFn::from(move || random.borrow_mut().f64())),
+            )?;
+
+            let crypto = Object::new(cx.clone())?;
+            crypto.set(
+                "getRandomValues",
+                Function::new(cx.clone(), move |cx, args| {
+                    let (cx, args) = hold_and_release!(cx, args);
+                    get_random_values(hold!(cx.clone(), args), &rng).map_err(|e| to_js_error(cx, e))
+                }),
+            )?;
+            globals.set("crypto", crypto)?;
+        }
+        None => math.set("random", Func::from(fastrand::f64))?,
+    }
 
     Ok::<_, Error>(())
 }
 
+/// Fills the integer typed array passed to `crypto.getRandomValues` with bytes
+/// from `rng` and returns it.
+fn get_random_values<'js>(args: Args<'js>, rng: &RefCell<fastrand::Rng>) -> Result<Value<'js>> {
+    let (_, args) = args.release();
+    let array = args.first().cloned().ok_or_else(|| {
+        anyhow!("crypto.getRandomValues expects 1 parameter: an integer typed array")
+    })?;
+    let object = array
+        .as_object()
+        .ok_or_else(|| anyhow!("Data needs to be an integer typed array"))?;
+    let raw = [
+        object.as_typed_array::<i8>().map(|a| a.as_raw()),
+        object.as_typed_array::<u8>().map(|a| a.as_raw()),
+        object.as_typed_array::<i16>().map(|a| a.as_raw()),
+        object.as_typed_array::<u16>().map(|a| a.as_raw()),
+        object.as_typed_array::<i32>().map(|a| a.as_raw()),
+        object.as_typed_array::<u32>().map(|a| a.as_raw()),
+        object.as_typed_array::<i64>().map(|a| a.as_raw()),
+        object.as_typed_array::<u64>().map(|a| a.as_raw()),
+    ]
+    .into_iter()
+    .flatten()
+    .next()
+    .ok_or_else(|| anyhow!("Data needs to be an integer typed array"))?
+    .ok_or_else(|| anyhow!("Data can't be a detached typed array"))?;
+    if raw.len > MAX_RANDOM_VALUES_BYTES {
+        bail!("Data can't be longer than {MAX_RANDOM_VALUES_BYTES} bytes");
+    }
+    // SAFETY: the bytes are the contents of the typed array, which is kept
+    // alive by `array` and isn't accessed elsewhere while they're filled.
+    let bytes = unsafe { slice::from_raw_parts_mut(raw.ptr.as_ptr(), raw.len) };
+    rng.borrow_mut().fill(bytes);
+    Ok(array)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        Runtime,
+        Config, Runtime,
         quickjs::{Value, context::EvalOptions},
     };
     use anyhow::{Error, Result};
+    use std::time::UNIX_EPOCH;
 
     #[test]
     fn test_random() -> Result<()> {
@@ -38,4 +110,69 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_seeded_random() -> Result<()> {
+        let sample = |seed| -> Result<Vec<f64>> {
+            let mut config = Config::default();
+            config.deterministic(seed, UNIX_EPOCH);
+            let runtime = Runtime::new(config)?;
+            runtime
+                .context()
+                .with(|this| this.eval("[Math.random(), Math.random(), Math.random()]"))
+                .map_err(Into::into)
+        };
+
+        assert_eq!(sample(42)?, sample(42)?);
+        assert_ne!(sample(42)?, sample(43)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_seeded_get_random_values() -> Result<()> {
+        let sample = |seed| -> Result<Vec<u32>> {
+            let mut config = Config::default();
+            config.deterministic(seed, UNIX_EPOCH);
+            let runtime = Runtime::new(config)?;
+            runtime
+                .context()
+                .with(|this| {
+                    this.eval(
+                        "const array = new Uint32Array(4); \
+                         crypto.getRandomValues(array) === array && Array.from(array)",
+                    )
+                })
+                .map_err(Into::into)
+        };
+
+        assert_eq!(sample(42)?, sample(42)?);
+        assert_ne!(sample(42)?, sample(43)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_random_values_rejects_invalid_arrays() -> Result<()> {
+        let mut config = Config::default();
+        config.deterministic(42, UNIX_EPOCH);
+        let runtime = Runtime::new(config)?;
+        runtime.context().with(|this| {
+            for array in ["new Float64Array(1)", "[1, 2]", "new Uint8Array(65537)"] {
+                let result: Value = this.eval(format!(
+                    "try {{ crypto.getRandomValues({array}); false }} catch {{ true }}"
+                ))?;
+                assert!(result.as_bool().unwrap(), "{array} should be rejected");
+            }
+            Ok::<_, Error>(())
+        })
+    }
+
+    #[test]
+    fn test_unseeded_random_without_crypto() -> Result<()> {
+        let runtime = Runtime::default();
+        let has_crypto: bool = runtime
+            .context()
+            .with(|this| this.eval("typeof crypto !== 'undefined'"))?;
+        assert!(!has_crypto);
+        Ok(())
+    }
 }
@@ -0,0 +1,28 @@
+use crate::quickjs::{Ctx, Value};
+use anyhow::Result;
+use rquickjs_serde::{de::Deserializer, ser::Serializer};
+use serde_transcode::Transcoder;
+
+/// Transcodes a byte slice containing a CBOR encoded payload into a [`Value`].
+///
+/// The payload is transcoded as it's decoded. Tags are ignored, so tagged
+/// items are transcoded as the item they tag.
+///
+/// Arguments:
+/// * `context` - A reference to the [`Ctx`] that will contain the returned
+///   [`Value`].
+/// * `bytes` - A byte slice containing a CBOR encoded payload.
+pub fn transcode_input<'js>(context: Ctx<'js>, bytes: &[u8]) -> Result<Value<'js>> {
+    let mut deserializer = ciborium::de::Deserializer::from_reader(bytes);
+    let mut serializer = Serializer::from_context(context.clone())?;
+    let value = serde_transcode::transcode(&mut deserializer, &mut serializer)?;
+    Ok(value)
+}
+
+/// Transcodes a [`Value`] into a CBOR encoded byte vector.
+pub fn transcode_output(val: Value<'_>) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut deserializer = Deserializer::from(val).with_strict();
+    ciborium::into_writer(&Transcoder::new(&mut deserializer), &mut output)?;
+    Ok(output)
+}
@@ -1,6 +1,7 @@
 use std::{
     fmt::Debug,
     io::{self, Write},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{Result, bail};
@@ -8,7 +9,7 @@ use bitflags::bitflags;
 
 bitflags! {
     /// Flags to represent available JavaScript features.
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy)]
     pub(crate) struct JSIntrinsics: u32  {
         const DATE = 1;
         const EVAL = 1 << 1;
@@ -43,12 +44,24 @@ bitflags! {
     /// users to extend the runtime with non-standard functionality directly
     /// from the CLI, at this point many, if not most, of these APIs will be
     /// moved out.
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy)]
     pub(crate) struct JavyIntrinsics: u32 {
         const STREAM_IO = 1;
+        const EXIT = 1 << 1;
+        const CBOR = 1 << 2;
     }
 }
 
+/// Settings for running programs deterministically.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Deterministic {
+    /// The seed used for `Math.random` and `crypto.getRandomValues`.
+    pub(crate) seed: u64,
+    /// The time reported by `Date` and `performance.timeOrigin`, in
+    /// milliseconds since the Unix epoch.
+    pub(crate) fixed_time: f64,
+}
+
 /// A configuration for [`Runtime`](crate::Runtime).
 ///
 /// These are the global configuration options to create a [`Runtime`](crate::Runtime),
@@ -76,6 +89,12 @@ pub struct Config {
     pub(crate) log_stream: Box<dyn Write>,
     /// The stream to use for calls to `console.error`.
     pub(crate) err_stream: Box<dyn Write>,
+    /// Whether sources of non-determinism are replaced with reproducible
+    /// ones. Default is `None`.
+    pub(crate) deterministic: Option<Deterministic>,
+    /// The number of operations after which execution is interrupted. Default
+    /// is `None`, meaning execution is never interrupted.
+    pub(crate) interrupt_after: Option<u64>,
 }
 
 impl Default for Config {
@@ -94,6 +113,8 @@ impl Default for Config {
             max_stack_size: 256 * 1024, // from rquickjs
             log_stream: Box::new(std::io::stdout()),
             err_stream: Box::new(std::io::stderr()),
+            deterministic: None,
+            interrupt_after: None,
         }
     }
 }
@@ -143,6 +164,13 @@ impl Config {
         self
     }
 
+    /// Configures whether the typed array intrinsics (e.g., `ArrayBuffer`
+    /// and `Uint8Array`) will be available.
+    pub fn typed_array(&mut self, enable: bool) -> &mut Self {
+        self.intrinsics.set(JSIntrinsics::TYPED_ARRAY, enable);
+        self
+    }
+
     /// Configures whether the `Promise` instrinsic will be available.
     pub fn promise(&mut self, enable: bool) -> &mut Self {
         self.intrinsics.set(JSIntrinsics::PROMISE, enable);
@@ -179,6 +207,28 @@ impl Config {
         self
     }
 
+    /// Whether the `Javy.exit` intrinsic will be available.
+    /// Disabled by default. `Javy.exit(code)` terminates execution with an
+    /// error that cannot be caught from JavaScript. Alternatively, programs
+    /// can set `Javy.exitCode` to report an exit code once execution
+    /// completes. Use [`Runtime::exit_code`](crate::Runtime::exit_code) to
+    /// retrieve the requested exit code.
+    pub fn javy_exit(&mut self, enable: bool) -> &mut Self {
+        self.javy_intrinsics.set(JavyIntrinsics::EXIT, enable);
+        self
+    }
+
+    /// Whether the `Javy.CBOR` intrinsic will be available.
+    /// Disabled by default. `Javy.CBOR.encode(value)` encodes a value as CBOR
+    /// in a `Uint8Array` and `Javy.CBOR.decode(data)` decodes the CBOR in an
+    /// `ArrayBuffer` or `Uint8Array`. This setting requires the `cbor` crate
+    /// feature to be enabled.
+    #[cfg(feature = "cbor")]
+    pub fn javy_cbor(&mut self, enable: bool) -> &mut Self {
+        self.javy_intrinsics.set(JavyIntrinsics::CBOR, enable);
+        self
+    }
+
     /// Enables whether the output of console.log will be redirected to
     /// `stderr`.
     pub fn redirect_stdout_to_stderr(&mut self, enable: bool) -> &mut Self {
@@ -223,6 +273,22 @@ impl Config {
         self
     }
 
+    /// The number of operations after which execution is interrupted.
+    /// Default is unlimited.
+    ///
+    /// Operations are counted by QuickJS's interrupt handler, which runs
+    /// once every 10,000 function calls and loop iterations, so `ops` is
+    /// rounded up to the next multiple of 10,000, and budgets below 10,000,
+    /// including `0`, allow 10,000 operations. Once the budget is exhausted,
+    /// execution is terminated with an error that can't be caught from
+    /// JavaScript and is reported as [`Interrupted`](crate::Interrupted) by
+    /// [`from_js_error`](crate::from_js_error). The budget is shared by all
+    /// executions in the [`Runtime`](crate::Runtime) and is not replenished.
+    pub fn interrupt_after(&mut self, ops: u64) -> &mut Self {
+        self.interrupt_after = Some(ops);
+        self
+    }
+
     /// The stream to use for calls to `console.log`.
     pub fn log_stream(&mut self, stream: Box<dyn Write>) -> &mut Self {
         self.log_stream = stream;
@@ -247,6 +313,26 @@ impl Config {
         self
     }
 
+    /// Makes the runtime's sources of non-determinism reproducible.
+    ///
+    /// `Math.random` is seeded with `seed`, `Date.now()` and `new Date()`
+    /// always report `fixed_time`, and `performance.now()` always reports
+    /// `0` with `performance.timeOrigin` set to `fixed_time`.
+    /// `crypto.getRandomValues` is defined and fills integer typed arrays
+    /// from the same seeded generator as `Math.random`.
+    ///
+    /// This is useful to replay inputs and get byte-for-byte identical
+    /// outputs. It must not be enabled for programs relying on
+    /// `Math.random` or `crypto.getRandomValues` for security.
+    pub fn deterministic(&mut self, seed: u64, fixed_time: SystemTime) -> &mut Self {
+        let fixed_time = match fixed_time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_millis() as f64,
+            Err(e) => -(e.duration().as_millis() as f64),
+        };
+        self.deterministic = Some(Deterministic { seed, fixed_time });
+        self
+    }
+
     pub(crate) fn validate(self) -> Result<Self> {
         if self.simd_json_builtins && !self.intrinsics.contains(JSIntrinsics::JSON) {
             bail!("JSON Intrinsic is required to override JSON.parse and JSON.stringify");
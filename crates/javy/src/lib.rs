@@ -45,11 +45,15 @@
 //!   byte slices
 //! * `messagepack` - functions for converting between [`quickjs::JSValueRef`]
 //!   and MessagePack byte slices
+//! * `cbor` - functions for converting between [`quickjs::Value`] and CBOR
+//!   byte slices, and the `Javy.CBOR` API
+//! * `serde-bridge` - functions for converting between [`quickjs::Value`] and
+//!   Rust values implementing `Serialize` and `Deserialize`
 
 pub use config::*;
 pub use rquickjs as quickjs;
 pub use runtime::Runtime;
-use std::str;
+use std::{fmt, str};
 
 mod config;
 mod runtime;
@@ -66,8 +70,21 @@ pub mod messagepack;
 #[cfg(feature = "json")]
 pub mod json;
 
+#[cfg(feature = "cbor")]
+pub mod cbor;
+
+#[cfg(feature = "serde-bridge")]
+pub mod serde;
+
 mod apis;
 
+/// The version of the QuickJS bytecode format compiled and evaluated by this
+/// version of Javy.
+///
+/// Incremented whenever upgrading QuickJS changes the bytecode format, so
+/// plugins can refuse to evaluate bytecode compiled for another version.
+pub const BYTECODE_VERSION: u32 = 1;
+
 /// A struct to hold the current [`Ctx`] and [`Value`]s passed as arguments to Rust
 /// functions.
 /// A struct here is used to explicitly tie these values with a particular
@@ -104,12 +121,38 @@ macro_rules! hold {
     };
 }
 
+/// The error reported when execution exceeds the budget configured through
+/// [`Config::interrupt_after`].
+///
+/// Use [`anyhow::Error::downcast_ref`] to tell it apart from JavaScript
+/// exceptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interrupted;
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Execution interrupted: operation budget exceeded")
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
 /// Handles a JavaScript error or exception and converts to [anyhow::Error].
+///
+/// Exceptions raised by QuickJS's interrupt handler are converted to
+/// [`Interrupted`].
 pub fn from_js_error(ctx: Ctx<'_>, e: JSError) -> Error {
     if e.is_exception() {
         let val = ctx.catch();
 
         if let Some(exception) = val.clone().into_exception() {
+            // QuickJS raises an uncatchable `InternalError` when interrupted.
+            // Uncatchable errors can't be created from JavaScript.
+            if unsafe { qjs::JS_IsUncatchableError(val.as_raw()) }
+                && exception.message().as_deref() == Some("interrupted")
+            {
+                return Interrupted.into();
+            }
             anyhow!("{exception}")
         } else {
             anyhow!(val_to_string(&ctx, val).unwrap_or_else(|_| "Internal error".to_string()))
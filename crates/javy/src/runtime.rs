@@ -1,19 +1,30 @@
 // use crate::quickjs::JSContextRef;
 use super::from_js_error;
+#[cfg(feature = "cbor")]
+use crate::apis::cbor;
 #[cfg(feature = "json")]
 use crate::apis::json;
 use crate::{
-    Config,
-    apis::{console, random, stream_io, text_encoding},
-    config::{JSIntrinsics, JavyIntrinsics},
+    Config, Interrupted,
+    apis::{
+        clock, console,
+        exit::{self, ExitState},
+        random, stream_io, text_encoding,
+    },
+    config::{Deterministic, JSIntrinsics, JavyIntrinsics},
 };
 
 use anyhow::{Result, bail};
+use bitflags::Flags;
 use rquickjs::{
-    Context, Module, Runtime as QRuntime, WriteOptions,
+    Context, Ctx, Module, Runtime as QRuntime, WriteOptions,
     context::{Intrinsic, intrinsic},
 };
-use std::mem::ManuallyDrop;
+use std::{cell::Cell, mem::ManuallyDrop, rc::Rc};
+
+/// The number of times QuickJS polls for interrupts between calls to the
+/// interrupt handler.
+const OPS_PER_INTERRUPT_CHECK: u64 = 10_000;
 
 /// A JavaScript Runtime.
 ///
@@ -36,21 +47,96 @@ pub struct Runtime {
     /// The inner QuickJS runtime representation.
     // Read above on the usage of `ManuallyDrop`.
     inner: ManuallyDrop<QRuntime>,
+    /// The exit code requested through the `Javy` namespace.
+    exit: Rc<ExitState>,
+    /// Whether the operation budget has been exhausted.
+    interrupted: Rc<Cell<bool>>,
+    /// The settings the runtime is currently configured with.
+    settings: Settings,
+}
+
+/// The parts of a [`Config`] that [`Runtime::reconfigure`] can change or
+/// depends on.
+#[derive(Debug, Clone, Copy)]
+struct Settings {
+    intrinsics: JSIntrinsics,
+    javy_intrinsics: JavyIntrinsics,
+    simd_json_builtins: bool,
+    gc_threshold: usize,
+    memory_limit: usize,
+    max_stack_size: usize,
+    deterministic: Option<Deterministic>,
+}
+
+impl Settings {
+    fn from_config(config: &Config) -> Self {
+        Self {
+            intrinsics: config.intrinsics,
+            javy_intrinsics: config.javy_intrinsics,
+            simd_json_builtins: config.simd_json_builtins,
+            gc_threshold: config.gc_threshold,
+            memory_limit: config.memory_limit,
+            max_stack_size: config.max_stack_size,
+            deterministic: config.deterministic,
+        }
+    }
+
+    fn apply_to(&self, config: &mut Config) {
+        config.intrinsics = self.intrinsics;
+        config.javy_intrinsics = self.javy_intrinsics;
+        config.simd_json_builtins = self.simd_json_builtins;
+        config.gc_threshold = self.gc_threshold;
+        config.memory_limit = self.memory_limit;
+        config.max_stack_size = self.max_stack_size;
+        config.deterministic = self.deterministic;
+    }
+}
+
+/// Joins the names of `flags` for error messages.
+fn names<F: Flags>(flags: F) -> String {
+    flags
+        .iter_names()
+        .map(|(name, _)| name.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 impl Runtime {
     /// Creates a new [Runtime].
     pub fn new(config: Config) -> Result<Self> {
         let rt = ManuallyDrop::new(QRuntime::new()?);
+        let exit = Rc::new(ExitState::default());
+        let interrupted = Rc::new(Cell::new(false));
+
+        if let Some(ops) = config.interrupt_after {
+            let interrupted = interrupted.clone();
+            // The budget is rounded up to a whole number of interrupt checks,
+            // and execution is always allowed to reach the first check.
+            let mut remaining_checks = ops.div_ceil(OPS_PER_INTERRUPT_CHECK).max(1);
+            rt.set_interrupt_handler(Some(Box::new(move || {
+                remaining_checks = remaining_checks.saturating_sub(1);
+                interrupted.set(remaining_checks == 0);
+                interrupted.get()
+            })));
+        }
 
-        let context = Self::build_from_config(&rt, config)?;
-        Ok(Self { inner: rt, context })
+        let settings = Settings::from_config(&config);
+        let context = Self::build_from_config(&rt, config, exit.clone())?;
+        Ok(Self {
+            inner: rt,
+            context,
+            exit,
+            interrupted,
+            settings,
+        })
     }
 
-    fn build_from_config(rt: &QRuntime, cfg: Config) -> Result<ManuallyDrop<Context>> {
+    fn build_from_config(
+        rt: &QRuntime,
+        cfg: Config,
+        exit_state: Rc<ExitState>,
+    ) -> Result<ManuallyDrop<Context>> {
         let cfg = cfg.validate()?;
-        let intrinsics = &cfg.intrinsics;
-        let javy_intrinsics = &cfg.javy_intrinsics;
 
         rt.set_gc_threshold(cfg.gc_threshold);
         rt.set_memory_limit(cfg.memory_limit);
@@ -67,72 +153,166 @@ impl Runtime {
             // NB: Users of Javy as a crate are welcome to switch this config,
             // however note that the usage of a custom `Random` implementation
             // should not affect the output of `Math.random()`.
-            random::register(ctx.clone()).expect("registering `random` APIs to succeed");
+            random::register(ctx.clone(), cfg.deterministic.map(|d| d.seed))
+                .expect("registering `random` APIs to succeed");
 
-            if intrinsics.contains(JSIntrinsics::DATE) {
-                unsafe { intrinsic::Date::add_intrinsic(ctx.as_raw()) }
-            }
+            Self::add_intrinsics(&ctx, cfg.intrinsics, cfg.simd_json_builtins);
 
-            if intrinsics.contains(JSIntrinsics::EVAL) {
-                unsafe { intrinsic::Eval::add_intrinsic(ctx.as_raw()) }
+            if let Some(deterministic) = cfg.deterministic {
+                clock::register(ctx.clone(), deterministic.fixed_time)
+                    .expect("registering deterministic clock to succeed");
             }
 
-            if intrinsics.contains(JSIntrinsics::REGEXP_COMPILER) {
-                unsafe { intrinsic::RegExpCompiler::add_intrinsic(ctx.as_raw()) }
-            }
+            console::register(ctx.clone(), cfg.log_stream, cfg.err_stream)
+                .expect("registering console to succeed");
 
-            if intrinsics.contains(JSIntrinsics::REGEXP) {
-                unsafe { intrinsic::RegExp::add_intrinsic(ctx.as_raw()) }
-            }
+            Self::add_javy_intrinsics(&ctx, cfg.javy_intrinsics, exit_state);
+        });
 
-            if intrinsics.contains(JSIntrinsics::JSON) {
-                unsafe { intrinsic::Json::add_intrinsic(ctx.as_raw()) }
-            }
+        Ok(ManuallyDrop::new(context))
+    }
 
-            #[cfg(feature = "json")]
-            if cfg.simd_json_builtins {
-                json::register(ctx.clone()).expect("registering JSON builtins to succeed");
-            }
+    /// Adds the JavaScript intrinsics in `intrinsics` to the context, and the
+    /// SIMD JSON builtins if `simd_json_builtins` is set.
+    #[cfg_attr(not(feature = "json"), allow(unused_variables))]
+    fn add_intrinsics(ctx: &Ctx<'_>, intrinsics: JSIntrinsics, simd_json_builtins: bool) {
+        if intrinsics.contains(JSIntrinsics::DATE) {
+            unsafe { intrinsic::Date::add_intrinsic(ctx.as_raw()) }
+        }
 
-            if intrinsics.contains(JSIntrinsics::PROXY) {
-                unsafe { intrinsic::Proxy::add_intrinsic(ctx.as_raw()) }
-            }
+        if intrinsics.contains(JSIntrinsics::EVAL) {
+            unsafe { intrinsic::Eval::add_intrinsic(ctx.as_raw()) }
+        }
 
-            if intrinsics.contains(JSIntrinsics::MAP_SET) {
-                unsafe { intrinsic::MapSet::add_intrinsic(ctx.as_raw()) }
-            }
+        if intrinsics.contains(JSIntrinsics::REGEXP_COMPILER) {
+            unsafe { intrinsic::RegExpCompiler::add_intrinsic(ctx.as_raw()) }
+        }
 
-            if intrinsics.contains(JSIntrinsics::TYPED_ARRAY) {
-                unsafe { intrinsic::TypedArrays::add_intrinsic(ctx.as_raw()) }
-            }
+        if intrinsics.contains(JSIntrinsics::REGEXP) {
+            unsafe { intrinsic::RegExp::add_intrinsic(ctx.as_raw()) }
+        }
 
-            if intrinsics.contains(JSIntrinsics::PROMISE) {
-                unsafe { intrinsic::Promise::add_intrinsic(ctx.as_raw()) }
-            }
+        if intrinsics.contains(JSIntrinsics::JSON) {
+            unsafe { intrinsic::Json::add_intrinsic(ctx.as_raw()) }
+        }
 
-            if intrinsics.contains(JSIntrinsics::TEXT_ENCODING) {
-                text_encoding::register(ctx.clone())
-                    .expect("registering TextEncoding APIs to succeed");
-            }
+        #[cfg(feature = "json")]
+        if simd_json_builtins {
+            json::register(ctx.clone()).expect("registering JSON builtins to succeed");
+        }
 
-            if intrinsics.contains(JSIntrinsics::WEAK_REF) {
-                unsafe { intrinsic::WeakRef::add_intrinsic(ctx.as_raw()) };
-            }
+        if intrinsics.contains(JSIntrinsics::PROXY) {
+            unsafe { intrinsic::Proxy::add_intrinsic(ctx.as_raw()) }
+        }
 
-            if intrinsics.contains(JSIntrinsics::PERFORMANCE) {
-                unsafe { intrinsic::Performance::add_intrinsic(ctx.as_raw()) };
-            }
+        if intrinsics.contains(JSIntrinsics::MAP_SET) {
+            unsafe { intrinsic::MapSet::add_intrinsic(ctx.as_raw()) }
+        }
 
-            console::register(ctx.clone(), cfg.log_stream, cfg.err_stream)
-                .expect("registering console to succeed");
+        if intrinsics.contains(JSIntrinsics::TYPED_ARRAY) {
+            unsafe { intrinsic::TypedArrays::add_intrinsic(ctx.as_raw()) }
+        }
+
+        if intrinsics.contains(JSIntrinsics::PROMISE) {
+            unsafe { intrinsic::Promise::add_intrinsic(ctx.as_raw()) }
+        }
+
+        if intrinsics.contains(JSIntrinsics::TEXT_ENCODING) {
+            text_encoding::register(ctx.clone()).expect("registering TextEncoding APIs to succeed");
+        }
+
+        if intrinsics.contains(JSIntrinsics::WEAK_REF) {
+            unsafe { intrinsic::WeakRef::add_intrinsic(ctx.as_raw()) };
+        }
+
+        if intrinsics.contains(JSIntrinsics::PERFORMANCE) {
+            unsafe { intrinsic::Performance::add_intrinsic(ctx.as_raw()) };
+        }
+    }
+
+    /// Adds the APIs in `javy_intrinsics` to the `Javy` namespace.
+    fn add_javy_intrinsics(
+        ctx: &Ctx<'_>,
+        javy_intrinsics: JavyIntrinsics,
+        exit_state: Rc<ExitState>,
+    ) {
+        if javy_intrinsics.contains(JavyIntrinsics::STREAM_IO) {
+            stream_io::register(ctx.clone()).expect("registering StreamIO functions to succeed");
+        }
+
+        if javy_intrinsics.contains(JavyIntrinsics::EXIT) {
+            exit::register(ctx.clone(), exit_state).expect("registering exit to succeed");
+        }
+
+        #[cfg(feature = "cbor")]
+        if javy_intrinsics.contains(JavyIntrinsics::CBOR) {
+            cbor::register(ctx.clone()).expect("registering CBOR functions to succeed");
+        }
+    }
 
-            if javy_intrinsics.contains(JavyIntrinsics::STREAM_IO) {
-                stream_io::register(ctx.clone())
-                    .expect("registering StreamIO functions to succeed");
+    /// Changes the configuration of the runtime.
+    ///
+    /// `configure` is called with a [`Config`] reflecting the current
+    /// configuration of the runtime. The intrinsics and APIs it enables are
+    /// added to the context and its limits are applied. Intrinsics and APIs
+    /// can't be removed from a context, so an error is returned if any of the
+    /// enabled ones are disabled. Options that only apply when creating a
+    /// runtime, such as the log streams, are ignored.
+    pub fn reconfigure<F>(&mut self, configure: F) -> Result<()>
+    where
+        F: FnOnce(&mut Config),
+    {
+        let mut config = Config::default();
+        self.settings.apply_to(&mut config);
+        configure(&mut config);
+        let settings = Settings::from_config(&config.validate()?);
+
+        let removed = self.settings.intrinsics.difference(settings.intrinsics);
+        if !removed.is_empty() {
+            bail!(
+                "Can't disable intrinsics of an existing runtime: {}",
+                names(removed)
+            );
+        }
+        let removed = self
+            .settings
+            .javy_intrinsics
+            .difference(settings.javy_intrinsics);
+        if !removed.is_empty() {
+            bail!(
+                "Can't disable Javy APIs of an existing runtime: {}",
+                names(removed)
+            );
+        }
+        if self.settings.simd_json_builtins && !settings.simd_json_builtins {
+            bail!("Can't disable the SIMD JSON builtins of an existing runtime");
+        }
+
+        self.inner.set_gc_threshold(settings.gc_threshold);
+        self.inner.set_memory_limit(settings.memory_limit);
+        self.inner.set_max_stack_size(settings.max_stack_size);
+
+        let added = settings.intrinsics.difference(self.settings.intrinsics);
+        let added_javy = settings
+            .javy_intrinsics
+            .difference(self.settings.javy_intrinsics);
+        let add_simd_json_builtins =
+            settings.simd_json_builtins && !self.settings.simd_json_builtins;
+        let exit_state = self.exit.clone();
+        self.context.with(|ctx| {
+            Self::add_intrinsics(&ctx, added, add_simd_json_builtins);
+
+            if let Some(deterministic) = settings.deterministic
+                && added.intersects(JSIntrinsics::DATE | JSIntrinsics::PERFORMANCE)
+            {
+                clock::register(ctx.clone(), deterministic.fixed_time)
+                    .expect("registering deterministic clock to succeed");
             }
-        });
 
-        Ok(ManuallyDrop::new(context))
+            Self::add_javy_intrinsics(&ctx, added_javy, exit_state);
+        });
+        self.settings = settings;
+        Ok(())
     }
 
     /// A reference to the inner [Context].
@@ -150,6 +330,9 @@ impl Runtime {
                 }
 
                 if let Err(e) = result {
+                    if self.interrupted() {
+                        bail!(Interrupted)
+                    }
                     bail!("{e}")
                 }
             }
@@ -163,6 +346,25 @@ impl Runtime {
         self.inner.is_job_pending()
     }
 
+    /// Returns the exit code requested by the JavaScript program, if any.
+    ///
+    /// An exit code is requested either by calling `Javy.exit(code)` or by
+    /// assigning `Javy.exitCode`.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit.code.get()
+    }
+
+    /// Returns true if execution was terminated by a call to `Javy.exit`.
+    pub fn exit_requested(&self) -> bool {
+        self.exit.requested.get()
+    }
+
+    /// Returns true if execution was interrupted because the budget configured
+    /// through [`Config::interrupt_after`] was exhausted.
+    pub fn interrupted(&self) -> bool {
+        self.interrupted.get()
+    }
+
     /// Compiles the given module to bytecode.
     pub fn compile_to_bytecode(&self, name: &str, contents: &str) -> Result<Vec<u8>> {
         self.context()
@@ -0,0 +1,31 @@
+//! Conversions between Rust values and JS values using `serde`.
+
+use crate::quickjs::{Ctx, Value};
+use ::serde::{Serialize, de::DeserializeOwned};
+use anyhow::Result;
+use rquickjs_serde::{de::Deserializer, ser::Serializer};
+
+/// Converts a Rust value into a [`Value`].
+///
+/// Arguments:
+/// * `context` - A reference to the [`Ctx`] that will contain the returned
+///   [`Value`].
+/// * `value` - The Rust value to convert.
+pub fn to_js<'js, T>(context: Ctx<'js>, value: &T) -> Result<Value<'js>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut serializer = Serializer::from_context(context)?;
+    Ok(value.serialize(&mut serializer)?)
+}
+
+/// Converts a [`Value`] into a Rust value.
+///
+/// `undefined` is accepted where the Rust value is optional.
+pub fn from_js<T>(value: Value<'_>) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = Deserializer::from(value);
+    Ok(T::deserialize(&mut deserializer)?)
+}
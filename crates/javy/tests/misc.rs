@@ -40,3 +40,249 @@ fn json_stringify_cycle_checks() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn bytecode_version_matches_quickjs() -> anyhow::Result<()> {
+    use javy::{BYTECODE_VERSION, Config, Runtime};
+
+    // The `BC_VERSION` QuickJS writes as the first byte of serialized
+    // bytecode. If upgrading QuickJS changes it, `BYTECODE_VERSION` must be
+    // incremented and this mapping updated.
+    const QUICKJS_BC_VERSION: u8 = 21;
+    assert_eq!(1, BYTECODE_VERSION);
+
+    let rt = Runtime::new(Config::default())?;
+    let bytecode = rt.compile_to_bytecode("index.js", "export const a = 1;")?;
+    assert_eq!(Some(&QUICKJS_BC_VERSION), bytecode.first());
+    Ok(())
+}
+
+#[test]
+fn interrupt_after_budget_is_exhausted() -> anyhow::Result<()> {
+    use javy::{Config, Interrupted, Runtime, from_js_error};
+
+    let mut config = Config::default();
+    config.interrupt_after(1_000_000);
+    let rt = Runtime::new(config)?;
+
+    let err = rt.context().with(|this| {
+        this.eval::<(), _>("try { while (true) {} } catch (e) {}")
+            .map_err(|e| from_js_error(this.clone(), e))
+            .expect_err("execution to be interrupted")
+    });
+
+    assert_eq!(Some(&Interrupted), err.downcast_ref::<Interrupted>());
+    assert!(rt.interrupted());
+    Ok(())
+}
+
+#[test]
+fn interrupt_after_zero_budget_is_rounded_up() -> anyhow::Result<()> {
+    use javy::{Config, Interrupted, Runtime, from_js_error};
+
+    let mut config = Config::default();
+    config.interrupt_after(0);
+    let rt = Runtime::new(config)?;
+
+    let err = rt.context().with(|this| {
+        this.eval::<(), _>("while (true) {}")
+            .map_err(|e| from_js_error(this.clone(), e))
+            .expect_err("execution to be interrupted")
+    });
+
+    assert_eq!(Some(&Interrupted), err.downcast_ref::<Interrupted>());
+    assert!(rt.interrupted());
+    Ok(())
+}
+
+#[test]
+fn interrupt_after_budget_is_not_exhausted() -> anyhow::Result<()> {
+    use javy::{Config, Runtime};
+
+    let mut config = Config::default();
+    config.interrupt_after(1_000_000);
+    let rt = Runtime::new(config)?;
+
+    rt.context().with(|this| {
+        this.eval::<(), _>("for (let i = 0; i < 1000; i++) {}")
+            .expect("execution to complete")
+    });
+
+    assert!(!rt.interrupted());
+    Ok(())
+}
+
+#[test]
+fn reconfigure_adds_intrinsics() -> anyhow::Result<()> {
+    use javy::Runtime;
+
+    let mut rt = Runtime::default();
+    rt.context().with(|this| {
+        assert!(!this.eval::<bool, _>("typeof WeakRef === 'function'")?);
+        Ok::<_, anyhow::Error>(())
+    })?;
+
+    rt.reconfigure(|config| {
+        config.weak_ref(true).text_encoding(true);
+    })?;
+
+    rt.context().with(|this| {
+        assert!(this.eval::<bool, _>("typeof WeakRef === 'function'")?);
+        assert!(this.eval::<bool, _>("typeof TextEncoder === 'function'")?);
+        assert!(this.eval::<bool, _>("typeof Date === 'function'")?);
+        Ok::<_, anyhow::Error>(())
+    })
+}
+
+#[test]
+fn reconfigure_cannot_remove_intrinsics() -> anyhow::Result<()> {
+    use javy::Runtime;
+
+    let mut rt = Runtime::default();
+    let err = rt
+        .reconfigure(|config| {
+            config.eval(false).proxy(false);
+        })
+        .unwrap_err();
+    assert_eq!(
+        "Can't disable intrinsics of an existing runtime: eval, proxy",
+        err.to_string()
+    );
+    Ok(())
+}
+
+#[cfg(feature = "serde-bridge")]
+#[test]
+fn serde_round_trip() -> anyhow::Result<()> {
+    use javy::{
+        Runtime,
+        quickjs::Value,
+        serde::{from_js, to_js},
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+        label: Option<String>,
+    }
+
+    let rt = Runtime::default();
+    rt.context().with(|this| {
+        let point = Point {
+            x: 1,
+            y: 2,
+            label: None,
+        };
+        this.globals().set("point", to_js(this.clone(), &point)?)?;
+        assert!(this.eval::<bool, _>("point.x === 1 && point.y === 2")?);
+
+        let value: Value = this.eval("({ x: 3, y: 4, label: 'a' })")?;
+        assert_eq!(
+            Point {
+                x: 3,
+                y: 4,
+                label: Some("a".into()),
+            },
+            from_js(value)?
+        );
+
+        let value: Value = this.eval("({ x: 'a', y: 4 })")?;
+        assert!(from_js::<Point>(value).is_err());
+        Ok::<_, anyhow::Error>(())
+    })
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_transcoding() -> anyhow::Result<()> {
+    use javy::{Runtime, cbor, quickjs::Value};
+
+    let rt = Runtime::default();
+    rt.context().with(|this| {
+        let value: Value = this.eval("({ a: [1, 'two', true], b: null })")?;
+        let bytes = cbor::transcode_output(value)?;
+        this.globals()
+            .set("value", cbor::transcode_input(this.clone(), &bytes)?)?;
+        assert!(this.eval::<bool, _>(
+            "value.a[0] === 1 && value.a[1] === 'two' && value.a[2] === true && value.b === null"
+        )?);
+        Ok::<_, anyhow::Error>(())
+    })
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_transcoding_byte_strings() -> anyhow::Result<()> {
+    use javy::{Runtime, cbor};
+
+    let rt = Runtime::default();
+    rt.context().with(|this| {
+        // {"bytes": h'010203'}
+        let bytes = [0xa1, 0x65, b'b', b'y', b't', b'e', b's', 0x43, 1, 2, 3];
+        this.globals()
+            .set("value", cbor::transcode_input(this.clone(), &bytes)?)?;
+        assert!(this.eval::<bool, _>("Array.from(value.bytes).join() === '1,2,3'")?);
+        Ok::<_, anyhow::Error>(())
+    })
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_transcoding_integer_keys() -> anyhow::Result<()> {
+    use javy::{Runtime, cbor};
+
+    let rt = Runtime::default();
+    rt.context().with(|this| {
+        // {1: "one", -2: "minus two"}
+        let mut bytes = vec![0xa2, 0x01, 0x63];
+        bytes.extend_from_slice(b"one");
+        bytes.extend_from_slice(&[0x21, 0x69]);
+        bytes.extend_from_slice(b"minus two");
+        this.globals()
+            .set("value", cbor::transcode_input(this.clone(), &bytes)?)?;
+        assert!(this.eval::<bool, _>("value[1] === 'one' && value[-2] === 'minus two'")?);
+        Ok::<_, anyhow::Error>(())
+    })
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_transcoding_tags() -> anyhow::Result<()> {
+    use javy::{Runtime, cbor};
+
+    let rt = Runtime::default();
+    rt.context().with(|this| {
+        // Tags are ignored, so the epoch-based date time 1(1363896240)
+        // becomes its number of seconds.
+        let bytes = [0xc1, 0x1a, 0x51, 0x4b, 0x67, 0xb0];
+        this.globals()
+            .set("value", cbor::transcode_input(this.clone(), &bytes)?)?;
+        assert!(this.eval::<bool, _>("value === 1363896240")?);
+        Ok::<_, anyhow::Error>(())
+    })
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_api() -> anyhow::Result<()> {
+    use javy::{Config, Runtime};
+
+    let mut config = Config::default();
+    config.javy_cbor(true);
+    let rt = Runtime::new(config)?;
+    rt.context().with(|this| {
+        assert!(this.eval::<bool, _>(
+            r#"
+                const encoded = Javy.CBOR.encode({ a: [1, "two"] });
+                const decoded = Javy.CBOR.decode(encoded);
+                encoded instanceof Uint8Array
+                    && decoded.a[0] === 1
+                    && decoded.a[1] === "two"
+                    && Javy.CBOR.decode(encoded.buffer).a.length === 2
+            "#
+        )?);
+        Ok::<_, anyhow::Error>(())
+    })
+}
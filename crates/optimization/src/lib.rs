@@ -0,0 +1,137 @@
+//! The `wasm-opt` optimizations run on Javy plugins and generated modules.
+
+use std::{fs, str::FromStr};
+
+use anyhow::{Result, anyhow, bail};
+use strum::IntoEnumIterator;
+use wasm_opt::{OptimizationOptions, Pass, ShrinkLevel};
+
+/// The `wasm-opt` optimizations to run on a Wasm module.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Optimization {
+    /// Don't optimize.
+    None,
+    /// Aggressively optimize for speed without optimizing for size at the
+    /// expense of performance, equivalent to `wasm-opt -O3`.
+    #[default]
+    Speed,
+    /// Optimize for size, equivalent to `wasm-opt -Os`.
+    Size,
+    /// Aggressively optimize for size at the expense of performance,
+    /// equivalent to `wasm-opt -Oz`.
+    AggressiveSize,
+    /// Run the `wasm-opt` passes with the given names in order, for example
+    /// `dce` or `vacuum`.
+    Custom(Vec<String>),
+}
+
+impl Optimization {
+    /// Runs the optimizations on `wasm` and returns the optimized module.
+    ///
+    /// The `name` custom section is only kept if `debug_info` is `true`.
+    pub fn run(&self, wasm: &[u8], debug_info: bool) -> Result<Vec<u8>> {
+        let mut options = match self {
+            Self::None if debug_info => return Ok(wasm.to_vec()),
+            // Round-trips the module through `wasm-opt` to drop the `name`
+            // section.
+            Self::None => OptimizationOptions::new_opt_level_0(),
+            Self::Speed => {
+                let mut options = OptimizationOptions::new_opt_level_3();
+                options.shrink_level(ShrinkLevel::Level0);
+                options
+            }
+            Self::Size => OptimizationOptions::new_optimize_for_size(),
+            Self::AggressiveSize => OptimizationOptions::new_optimize_for_size_aggressively(),
+            Self::Custom(passes) => {
+                let mut options = OptimizationOptions::new_opt_level_0();
+                for pass in passes {
+                    options.add_pass(pass_from_name(pass)?);
+                }
+                options
+            }
+        };
+
+        let tempdir = tempfile::tempdir()?;
+        let tempfile_path = tempdir.path().join("temp.wasm");
+        fs::write(&tempfile_path, wasm)?;
+        options
+            .debug_info(debug_info)
+            .run(&tempfile_path, &tempfile_path)?;
+        Ok(fs::read(&tempfile_path)?)
+    }
+}
+
+impl FromStr for Optimization {
+    type Err = anyhow::Error;
+
+    /// Parses `none`, `speed`, `size`, `aggressive-size`, or a comma separated
+    /// list of `wasm-opt` passes.
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(Self::None),
+            "speed" => Ok(Self::Speed),
+            "size" => Ok(Self::Size),
+            "aggressive-size" => Ok(Self::AggressiveSize),
+            "" => bail!("Expected an optimization or a list of wasm-opt passes"),
+            passes => {
+                let passes = passes
+                    .split(',')
+                    .map(|pass| pass_from_name(pass).map(|_| pass.to_string()))
+                    .collect::<Result<_>>()?;
+                Ok(Self::Custom(passes))
+            }
+        }
+    }
+}
+
+fn pass_from_name(name: &str) -> Result<Pass> {
+    Pass::iter()
+        .find(|pass| pass.name() == name)
+        .ok_or_else(|| anyhow!("Unknown wasm-opt pass {name}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Optimization;
+    use anyhow::Result;
+    use wasmparser::{Parser, Payload};
+
+    #[test]
+    fn parse_optimization() -> Result<()> {
+        assert_eq!(Optimization::None, "none".parse()?);
+        assert_eq!(Optimization::Speed, "speed".parse()?);
+        assert_eq!(Optimization::Size, "size".parse()?);
+        assert_eq!(Optimization::AggressiveSize, "aggressive-size".parse()?);
+        assert_eq!(
+            Optimization::Custom(vec!["dce".into(), "vacuum".into()]),
+            "dce,vacuum".parse()?
+        );
+        assert_eq!(
+            "Unknown wasm-opt pass fast",
+            "fast".parse::<Optimization>().unwrap_err().to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn no_optimization_strips_name_section_without_debug_info() -> Result<()> {
+        let wasm = wat::parse_str("(module (func $foo) (export \"foo\" (func $foo)))")?;
+        assert!(has_name_section(&wasm)?);
+
+        assert_eq!(wasm, Optimization::None.run(&wasm, true)?);
+        let stripped = Optimization::None.run(&wasm, false)?;
+        assert!(!has_name_section(&stripped)?);
+        Ok(())
+    }
+
+    fn has_name_section(wasm: &[u8]) -> Result<bool> {
+        for payload in Parser::new(0).parse_all(wasm) {
+            if let Payload::CustomSection(reader) = payload?
+                && reader.name() == "name"
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
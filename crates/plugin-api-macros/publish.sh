@@ -0,0 +1,5 @@
+#!/usr/bin/env bash
+
+set -e
+
+cargo publish
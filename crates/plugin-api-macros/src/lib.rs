@@ -0,0 +1,283 @@
+//! Procedural macros for Javy plugins.
+//!
+//! These macros are re-exported by `javy-plugin-api` and shouldn't be used
+//! directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Error, Expr, ExprLit, Fields, FnArg, GenericArgument, ItemFn,
+    Lit, Meta, PathArguments, Result, ReturnType, Token, Type, parse_macro_input,
+    punctuated::Punctuated,
+};
+
+/// Implements `javy_plugin_api::runtime_config::RuntimeConfig` for a struct
+/// with named `Option` fields.
+///
+/// Each field is a property named after the field in kebab case, documented
+/// by the field's doc comment. Fields accept a `#[config(...)]` attribute with
+/// the following arguments:
+///
+/// * `default = <expr>` - the value used when the property isn't provided.
+/// * `values(<expr>, ...)` - the only values the property accepts.
+#[proc_macro_derive(RuntimeConfig, attributes(config))]
+pub fn derive_runtime_config(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_runtime_config(&input) {
+        Ok(tok) => tok.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+struct Property {
+    field: syn::Ident,
+    ty: Type,
+    doc: String,
+    default: Option<Expr>,
+    allowed_values: Option<Vec<Expr>>,
+}
+
+impl Property {
+    fn from_field(field: &syn::Field) -> Result<Self> {
+        let ident = field
+            .ident
+            .clone()
+            .ok_or_else(|| Error::new_spanned(field, "Expected a named field"))?;
+        let mut property = Self {
+            field: ident,
+            ty: option_inner_type(&field.ty)?,
+            doc: doc(&field.attrs),
+            default: None,
+            allowed_values: None,
+        };
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("config")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    property.default = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("values") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let values = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?;
+                    property.allowed_values = Some(values.into_iter().collect());
+                    Ok(())
+                } else {
+                    Err(meta.error("Unsupported config argument"))
+                }
+            })?;
+        }
+        Ok(property)
+    }
+
+    fn name(&self) -> String {
+        self.field.to_string().replace('_', "-")
+    }
+}
+
+/// Returns `T` for a field of type `Option<T>`.
+fn option_inner_type(ty: &Type) -> Result<Type> {
+    if let Type::Path(path) = ty
+        && let Some(segment) = path.path.segments.last()
+        && segment.ident == "Option"
+        && let PathArguments::AngleBracketed(args) = &segment.arguments
+        && let Some(GenericArgument::Type(inner)) = args.args.first()
+    {
+        return Ok(inner.clone());
+    }
+    Err(Error::new_spanned(ty, "Expected an `Option` type"))
+}
+
+/// Joins the lines of the doc comments in `attrs`.
+fn doc(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => Some(format!("{}\n", s.value())),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn expand_runtime_config(input: &DeriveInput) -> Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(input, "Expected a struct"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            input,
+            "Expected a struct with named fields",
+        ));
+    };
+    let properties = fields
+        .named
+        .iter()
+        .map(Property::from_field)
+        .collect::<Result<Vec<_>>>()?;
+
+    let defaults = properties.iter().filter_map(|property| {
+        let field = &property.field;
+        property.default.as_ref().map(|default| {
+            quote! {
+                if self.#field.is_none() {
+                    self.#field = Some(::core::convert::Into::into(#default));
+                }
+            }
+        })
+    });
+    let schema_properties = properties.iter().map(|property| {
+        let ty = &property.ty;
+        let name = property.name();
+        let doc = &property.doc;
+        let default = property.default.as_ref().map(|default| {
+            quote! { property.default(#default); }
+        });
+        let allowed_values = property.allowed_values.as_ref().map(|values| {
+            quote! { property.allowed_values([#(::javy_plugin_api::runtime_config::Value::from(#values)),*]); }
+        });
+        quote! {
+            {
+                let mut property =
+                    ::javy_plugin_api::runtime_config::ConfigProperty::new::<#ty>(#name, #doc);
+                #default
+                #allowed_values
+                property
+            }
+        }
+    });
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::javy_plugin_api::runtime_config::RuntimeConfig for #ident #ty_generics #where_clause {
+            fn with_defaults(mut self) -> Self {
+                #(#defaults)*
+                self
+            }
+
+            fn config_schema() -> ::javy_plugin_api::runtime_config::ConfigSchema {
+                ::javy_plugin_api::runtime_config::ConfigSchema::new(vec![#(#schema_properties),*])
+            }
+        }
+    })
+}
+
+/// Generates a `js_<name>` function creating a JavaScript function that calls
+/// the annotated function.
+///
+/// `&[u8]` parameters accept an `ArrayBuffer` or a `Uint8Array`. Other
+/// parameters are deserialized from their JavaScript values, and the return
+/// value is serialized to a JavaScript value, with `serde`. Errors returned in
+/// a `Result` are thrown as JavaScript errors.
+#[proc_macro_attribute]
+pub fn javy_fn(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return Error::new(
+            proc_macro2::Span::call_site(),
+            "`javy_fn` doesn't take arguments",
+        )
+        .into_compile_error()
+        .into();
+    }
+    let item = parse_macro_input!(item as ItemFn);
+    match expand_javy_fn(&item) {
+        Ok(tok) => tok.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+fn expand_javy_fn(item: &ItemFn) -> Result<TokenStream2> {
+    let sig = &item.sig;
+    if sig.asyncness.is_some() || !sig.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            sig,
+            "`javy_fn` only supports non-async functions without generics",
+        ));
+    }
+
+    let mut conversions = vec![];
+    let mut call_args = vec![];
+    for (index, input) in sig.inputs.iter().enumerate() {
+        let FnArg::Typed(pat_type) = input else {
+            return Err(Error::new_spanned(
+                input,
+                "`javy_fn` doesn't support methods",
+            ));
+        };
+        let arg = format_ident!("arg{index}");
+        match &*pat_type.ty {
+            Type::Reference(reference) if is_byte_slice(&reference.elem) => {
+                conversions.push(quote! {
+                    let #arg = ::javy_plugin_api::host_fn::bytes_arg(&cx, &args, #index)?;
+                });
+                call_args.push(quote! { #arg.as_bytes() });
+            }
+            Type::Reference(reference) => {
+                let ty = match &*reference.elem {
+                    Type::Path(path) if path.path.is_ident("str") => quote! { String },
+                    ty => quote! { #ty },
+                };
+                conversions.push(quote! {
+                    let #arg = ::javy_plugin_api::host_fn::arg::<#ty>(&cx, &args, #index)?;
+                });
+                call_args.push(quote! { &#arg });
+            }
+            ty => {
+                conversions.push(quote! {
+                    let #arg = ::javy_plugin_api::host_fn::arg::<#ty>(&cx, &args, #index)?;
+                });
+                call_args.push(quote! { #arg });
+            }
+        }
+    }
+
+    let name = &sig.ident;
+    let call = quote! { #name(#(#call_args),*) };
+    let ret = match &sig.output {
+        ReturnType::Default => quote! {
+            #call;
+            Ok(::javy_plugin_api::javy::quickjs::Value::new_undefined(cx))
+        },
+        ReturnType::Type(_, ty) if is_result(ty) => quote! {
+            ::javy_plugin_api::host_fn::result_into_js(&cx, #call)
+        },
+        ReturnType::Type(..) => quote! {
+            ::javy_plugin_api::host_fn::into_js(&cx, #call)
+        },
+    };
+
+    let vis = &item.vis;
+    let js_name = format_ident!("js_{name}");
+    let doc = format!("Creates a JavaScript function calling [`{name}`].");
+    Ok(quote! {
+        #item
+
+        #[doc = #doc]
+        #vis fn #js_name<'js>(
+            ctx: &::javy_plugin_api::javy::quickjs::Ctx<'js>,
+        ) -> ::javy_plugin_api::javy::quickjs::Result<::javy_plugin_api::javy::quickjs::Function<'js>> {
+            ::javy_plugin_api::javy::quickjs::Function::new(
+                ctx.clone(),
+                |cx: ::javy_plugin_api::javy::quickjs::Ctx<'js>,
+                 args: ::javy_plugin_api::javy::quickjs::prelude::Rest<::javy_plugin_api::javy::quickjs::Value<'js>>|
+                 -> ::javy_plugin_api::javy::quickjs::Result<::javy_plugin_api::javy::quickjs::Value<'js>> {
+                    #(#conversions)*
+                    #ret
+                },
+            )
+        }
+    })
+}
+
+fn is_byte_slice(ty: &Type) -> bool {
+    matches!(ty, Type::Slice(slice) if matches!(&*slice.elem, Type::Path(path) if path.path.is_ident("u8")))
+}
+
+fn is_result(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.segments.last().is_some_and(|s| s.ident == "Result"))
+}
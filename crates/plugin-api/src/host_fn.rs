@@ -0,0 +1,78 @@
+//! Conversions used by the functions generated by [`crate::javy_fn`].
+
+use anyhow::anyhow;
+use javy::quickjs::{self, ArrayBuffer, Ctx, TypedArray, Value, prelude::Rest};
+use javy::serde::{from_js, to_js};
+use javy::to_js_error;
+use serde::{Serialize, de::DeserializeOwned};
+
+/// The bytes of an `ArrayBuffer` or `Uint8Array` argument.
+pub enum BytesArg<'js> {
+    ArrayBuffer(ArrayBuffer<'js>),
+    Uint8Array(TypedArray<'js, u8>),
+}
+
+impl BytesArg<'_> {
+    /// The bytes of the argument, empty if its buffer was detached.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::ArrayBuffer(buffer) => buffer.as_bytes(),
+            Self::Uint8Array(array) => array.as_bytes(),
+        }
+        .unwrap_or_default()
+    }
+}
+
+/// Returns the argument at `index`, or `undefined` if it wasn't passed.
+fn value<'js>(cx: &Ctx<'js>, args: &Rest<Value<'js>>, index: usize) -> Value<'js> {
+    args.get(index)
+        .cloned()
+        .unwrap_or_else(|| Value::new_undefined(cx.clone()))
+}
+
+/// Converts the argument at `index` to an `ArrayBuffer` or `Uint8Array`.
+pub fn bytes_arg<'js>(
+    cx: &Ctx<'js>,
+    args: &Rest<Value<'js>>,
+    index: usize,
+) -> quickjs::Result<BytesArg<'js>> {
+    let value = value(cx, args, index);
+    if let Ok(buffer) = value.get::<ArrayBuffer>() {
+        Ok(BytesArg::ArrayBuffer(buffer))
+    } else if let Ok(array) = value.get::<TypedArray<u8>>() {
+        Ok(BytesArg::Uint8Array(array))
+    } else {
+        Err(to_js_error(
+            cx.clone(),
+            anyhow!("Argument {index} must be an ArrayBuffer or a Uint8Array"),
+        ))
+    }
+}
+
+/// Deserializes the argument at `index`.
+pub fn arg<'js, T: DeserializeOwned>(
+    cx: &Ctx<'js>,
+    args: &Rest<Value<'js>>,
+    index: usize,
+) -> quickjs::Result<T> {
+    from_js(value(cx, args, index))
+        .map_err(|e| to_js_error(cx.clone(), anyhow!("Invalid argument {index}: {e}")))
+}
+
+/// Serializes the return value of a function.
+pub fn into_js<'js, T: Serialize>(cx: &Ctx<'js>, value: T) -> quickjs::Result<Value<'js>> {
+    to_js(cx.clone(), &value).map_err(|e| to_js_error(cx.clone(), e))
+}
+
+/// Serializes the return value of a function returning a [`Result`], throwing
+/// its error.
+pub fn result_into_js<'js, T, E>(cx: &Ctx<'js>, result: Result<T, E>) -> quickjs::Result<Value<'js>>
+where
+    T: Serialize,
+    E: Into<anyhow::Error>,
+{
+    match result {
+        Ok(value) => into_js(cx, value),
+        Err(e) => Err(to_js_error(cx.clone(), e.into())),
+    }
+}
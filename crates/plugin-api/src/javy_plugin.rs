@@ -9,6 +9,16 @@
 ///   the [`javy::Runtime`].
 /// * `modify_runtime` - A function that can add modify the [`javy::Runtime`].
 ///   For example, by adding additional methods for use by JavaScript.
+/// * `call_export` - Optional. Also implements `call-export`, which must then
+///   be exported by the WIT world as
+///   `export call-export: func(function: string);`, so exported functions can
+///   be called without evaluating the module again.
+///
+/// With the `bytecode-only` feature, `compile-src` isn't implemented and must
+/// not be exported by the WIT world. With the `compressed-bytecode` feature,
+/// `invoke-compressed` is also implemented and must be exported by the WIT
+/// world as
+/// `export invoke-compressed: func(bytecode: list<u8>, function: option<string>);`.
 ///
 /// # Examples
 ///
@@ -30,12 +40,23 @@
 #[macro_export]
 macro_rules! javy_plugin {
     ($namespace:literal, $component:ident, $config:expr, $modify_runtime:expr) => {
+        javy_plugin_api::javy_plugin!(@impl $namespace, $component, $config, $modify_runtime, {});
+    };
+    ($namespace:literal, $component:ident, $config:expr, $modify_runtime:expr, call_export) => {
+        javy_plugin_api::javy_plugin!(@impl $namespace, $component, $config, $modify_runtime, {
+            fn call_export(function: String) -> () {
+                javy_plugin_api::call_export(&function).unwrap_or_else(|e| {
+                    eprintln!("{e}");
+                    std::process::abort();
+                });
+            }
+        });
+    };
+    (@impl $namespace:literal, $component:ident, $config:expr, $modify_runtime:expr, { $($extra:tt)* }) => {
         javy_plugin_api::import_namespace!($namespace);
 
         impl Guest for $component {
-            fn compile_src(src: Vec<u8>) -> Result<Vec<u8>, String> {
-                javy_plugin_api::compile_src(&src).map_err(|e| e.to_string())
-            }
+            javy_plugin_api::__compile_src!();
 
             fn initialize_runtime() -> () {
                 javy_plugin_api::initialize_runtime($config, $modify_runtime).unwrap();
@@ -47,6 +68,51 @@ macro_rules! javy_plugin {
                     std::process::abort();
                 });
             }
+
+            javy_plugin_api::__invoke_compressed!();
+
+            $($extra)*
+        }
+    };
+}
+
+#[cfg(not(feature = "bytecode-only"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __compile_src {
+    () => {
+        fn compile_src(src: Vec<u8>) -> Result<Vec<u8>, String> {
+            javy_plugin_api::compile_src(&src).map_err(|e| e.to_string())
+        }
+    };
+}
+
+#[cfg(feature = "bytecode-only")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __compile_src {
+    () => {};
+}
+
+#[cfg(feature = "compressed-bytecode")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __invoke_compressed {
+    () => {
+        fn invoke_compressed(bytecode: Vec<u8>, function: Option<String>) -> () {
+            javy_plugin_api::invoke_compressed(&bytecode, function.as_deref()).unwrap_or_else(
+                |e| {
+                    eprintln!("{e}");
+                    std::process::abort();
+                },
+            );
         }
     };
 }
+
+#[cfg(not(feature = "compressed-bytecode"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __invoke_compressed {
+    () => {};
+}
@@ -85,10 +85,37 @@
 //! * [`import_namespace`] - Used for WASI preview 1 plugins. Takes a namespace
 //!   to use for the module name for imports.
 //! * [`Config`] - to add behavior to the created [`javy::Runtime`].
+//! * [`configure_module`] - Used to apply the runtime config of dynamically
+//!   linked modules. Plugins supporting it export
+//!   `configure-module: func(config: list<u8>)`, which receives the module's
+//!   runtime config as JSON before its bytecode is evaluated.
+//! * [`javy_fn`] - Used to expose a Rust function to JavaScript. Generates a
+//!   `js_<name>` function creating a JavaScript function that converts its
+//!   arguments and return value.
+//! * [`runtime_config`] - Used to declare the runtime config properties
+//!   supported by the plugin with `#[derive(RuntimeConfig)]` and to receive the
+//!   runtime config passed with `-J` when the plugin is initialized.
+//! * [`config_schema`] - Used for WASI preview 1 plugins. Takes a struct
+//!   deriving `RuntimeConfig` and exports its schema as `config-schema`.
 //!
 //! # Features
 //! * `json` - enables the `json` feature in the `javy` crate.
 //! * `messagepack` - enables the `messagepack` feature in the `javy` crate.
+//! * `cbor` - enables the `cbor` feature in the `javy` crate.
+//! * `serde-bridge` - enables the `serde-bridge` feature in the `javy` crate.
+//! * `bytecode-only` - builds a plugin for statically linked modules that can
+//!   only execute bytecode. [`compile_src`] and the `compile-src` export are
+//!   removed and `eval` and the `Function` constructor are always disabled, so
+//!   the JS compiler can be stripped from the generated modules. JS must be
+//!   compiled with a plugin built without this feature.
+//! * `compressed-bytecode` - adds [`invoke_compressed`] and the
+//!   `invoke-compressed` export to evaluate Brotli compressed bytecode, so
+//!   modules can be generated with compressed bytecode.
+//! * `host-fn` - adds the [`javy_fn`] attribute macro. Also enables the
+//!   `serde-bridge` feature in the `javy` crate.
+//! * `runtime-config` - adds the [`runtime_config`] module and the
+//!   [`config_schema`] macro to declare and deserialize the runtime config
+//!   passed with `-J`.
 
 // Allow these in this file because we only run this program single threaded
 // and we can safely reason about the accesses to the Javy Runtime. We also
@@ -96,26 +123,43 @@
 #![allow(static_mut_refs)]
 use anyhow::{Result, anyhow, bail};
 pub use config::Config;
-use javy::quickjs::{self, Ctx, Error as JSError, Function, Module, Value};
+use javy::quickjs::{self, Ctx, Error as JSError, Function, Module, Object, Persistent, Value};
 use javy::{Runtime, from_js_error};
 use std::cell::OnceCell;
-use std::str;
+#[cfg(feature = "compressed-bytecode")]
+use std::io::Read;
+use std::io::{self, Write};
+use std::{mem, process, str};
 
 pub use javy;
+#[cfg(feature = "host-fn")]
+pub use javy_plugin_api_macros::javy_fn;
 
 mod config;
+#[cfg(feature = "host-fn")]
+#[doc(hidden)]
+pub mod host_fn;
 mod javy_plugin;
 mod namespace;
+#[cfg(feature = "runtime-config")]
+pub mod runtime_config;
 #[cfg(all(target_family = "wasm", target_os = "wasi", target_env = "p1"))]
 mod wasi_p1;
 
+#[cfg(not(feature = "bytecode-only"))]
 const FUNCTION_MODULE_NAME: &str = "function.mjs";
 
+/// The prefix of the header `javy-codegen` adds to the bytecode of dynamically
+/// linked modules.
+const BYTECODE_HEADER_MAGIC: &[u8] = b"\xffjavy";
+
 thread_local! {
     static COMPILE_SRC_RET_AREA: OnceCell<[u32; 2]> = const { OnceCell::new() }
 }
 
 static mut RUNTIME: OnceCell<Runtime> = OnceCell::new();
+/// The namespace of the most recently evaluated module.
+static mut EVALUATED_MODULE: Option<Persistent<Object<'static>>> = None;
 static mut EVENT_LOOP_ENABLED: bool = false;
 
 static EVENT_LOOP_ERR: &str = r#"
@@ -130,10 +174,17 @@ where
     F: FnOnce() -> Config,
     G: FnOnce(Runtime) -> Runtime,
 {
-    let config = config();
+    #[allow(unused_mut)]
+    let mut config = config();
+    // Bytecode-only plugins can't compile JS at runtime.
+    #[cfg(feature = "bytecode-only")]
+    config.runtime_config.eval(false);
     let runtime = Runtime::new(config.runtime_config)?;
     let runtime = modify_runtime(runtime);
     unsafe {
+        // The evaluated module must be released before the runtime it belongs
+        // to.
+        EVALUATED_MODULE.take();
         RUNTIME.take(); // Allow re-initializing.
         RUNTIME
             .set(runtime)
@@ -147,6 +198,41 @@ where
     Ok(())
 }
 
+/// Changes the configuration of the initialized runtime to the one requested
+/// by a dynamically linked module, before its bytecode is evaluated.
+///
+/// `configure` is called with a [`Config`] reflecting the current
+/// configuration. Intrinsics and APIs it enables are added to the runtime, but
+/// an error is returned if it disables any that are enabled since they can't
+/// be removed. See [`javy::Runtime::reconfigure`].
+///
+/// # Arguments
+///
+/// * `configure` - A function that modifies the config
+pub fn configure_module<F>(configure: F) -> Result<()>
+where
+    F: FnOnce(&mut Config),
+{
+    let runtime = unsafe { RUNTIME.get_mut() }.ok_or_else(|| {
+        anyhow!("Javy runtime not initialized. Ensure `javy init-plugin` has been invoked.")
+    })?;
+    let mut event_loop = unsafe { EVENT_LOOP_ENABLED };
+    runtime.reconfigure(|runtime_config| {
+        let mut config = Config {
+            runtime_config: mem::take(runtime_config),
+            event_loop,
+        };
+        configure(&mut config);
+        // Bytecode-only plugins can't compile JS at runtime.
+        #[cfg(feature = "bytecode-only")]
+        config.runtime_config.eval(false);
+        event_loop = config.event_loop;
+        *runtime_config = config.runtime_config;
+    })?;
+    unsafe { EVENT_LOOP_ENABLED = event_loop };
+    Ok(())
+}
+
 /// Compiles JS source code to QuickJS bytecode.
 ///
 /// Returns result with the success value being a vector of the bytecode and
@@ -157,6 +243,7 @@ where
 /// * `config` - A function that returns a config for Javy
 /// * `modify_runtime` - A function that returns a Javy runtime
 /// * `js_src` - A slice of bytes representing the JS source code
+#[cfg(not(feature = "bytecode-only"))]
 pub fn compile_src(js_src: &[u8]) -> Result<Vec<u8>> {
     // Use initialized runtime when compiling because certain runtime
     // configurations can cause different bytecode to be emitted.
@@ -180,33 +267,144 @@ pub fn compile_src(js_src: &[u8]) -> Result<Vec<u8>> {
 /// Evaluates QuickJS bytecode and optionally invokes exported JS function with
 /// name.
 ///
+/// Bytecode of dynamically linked modules starts with a header containing the
+/// version of the bytecode format, and an error is returned if it doesn't
+/// match [`javy::BYTECODE_VERSION`].
+///
+/// The namespace of the evaluated module is retained so its exported
+/// functions can be invoked with [`call_export`] without evaluating the
+/// bytecode again.
+///
+/// If the JavaScript program requests an exit code, either through
+/// `Javy.exit` or by setting `Javy.exitCode` to a non-zero value, the instance
+/// is terminated with that exit code and this function does not return.
+///
 /// # Arguments
 ///
 /// * `bytecode` - The QuickJS bytecode
 /// * `fn_name` - The JS function name
 pub fn invoke(bytecode: &[u8], fn_name: Option<&str>) -> Result<()> {
+    let bytecode = strip_bytecode_header(bytecode)?;
+    run(|this| {
+        let module = unsafe { Module::load(this.clone(), bytecode)? };
+        let (module, promise) = module.eval()?;
+
+        handle_maybe_promise(this.clone(), promise.into())?;
+
+        let namespace = module.namespace()?;
+        unsafe { EVALUATED_MODULE = Some(Persistent::save(&this, namespace.clone())) };
+
+        match fn_name {
+            Some(fn_name) => call_function(this, namespace, fn_name),
+            None => Ok(()),
+        }
+    })
+}
+
+/// Decompresses Brotli compressed QuickJS bytecode, then evaluates it and
+/// optionally invokes exported JS function with name like [`invoke`].
+///
+/// # Arguments
+///
+/// * `bytecode` - The Brotli compressed QuickJS bytecode
+/// * `fn_name` - The JS function name
+#[cfg(feature = "compressed-bytecode")]
+pub fn invoke_compressed(bytecode: &[u8], fn_name: Option<&str>) -> Result<()> {
+    let mut decompressed = vec![];
+    brotli::Decompressor::new(bytecode, 4096).read_to_end(&mut decompressed)?;
+    invoke(&decompressed, fn_name)
+}
+
+/// Checks and removes the header added to the bytecode of dynamically linked
+/// modules, if present.
+fn strip_bytecode_header(bytecode: &[u8]) -> Result<&[u8]> {
+    let Some(header) = bytecode.strip_prefix(BYTECODE_HEADER_MAGIC) else {
+        return Ok(bytecode);
+    };
+    let Some((version, bytecode)) = header.split_first_chunk::<4>() else {
+        bail!("Bytecode header is truncated");
+    };
+    let version = u32::from_le_bytes(*version);
+    if version != javy::BYTECODE_VERSION {
+        bail!(
+            "Module uses bytecode version {version} but the plugin supports version {}. \
+            Generate the module again with a compatible plugin.",
+            javy::BYTECODE_VERSION
+        );
+    }
+    Ok(bytecode)
+}
+
+/// Invokes the exported JS function with name on the module most recently
+/// evaluated by [`invoke`].
+///
+/// Exits the instance like [`invoke`] if the JavaScript program requests an
+/// exit code.
+///
+/// # Arguments
+///
+/// * `fn_name` - The JS function name
+pub fn call_export(fn_name: &str) -> Result<()> {
+    run(|this| {
+        let Some(namespace) = (unsafe { EVALUATED_MODULE.clone() }) else {
+            return Err(javy::to_js_error(
+                this,
+                anyhow!("No module has been evaluated. Ensure `invoke` has been called."),
+            ));
+        };
+        let namespace = namespace.restore(&this)?;
+        call_function(this, namespace, fn_name)
+    })
+}
+
+/// Runs `f` in the runtime's context, resolves pending jobs and terminates
+/// the instance if the JavaScript program requested an exit code.
+fn run<F>(f: F) -> Result<()>
+where
+    F: FnOnce(Ctx) -> quickjs::Result<()>,
+{
     let runtime = unsafe { RUNTIME.get() }.ok_or_else(|| {
         anyhow!("Javy runtime not initialized. Ensure `javy init-plugin` has been invoked.")
     })?;
-    runtime
+    let result = runtime
         .context()
-        .with(|this| {
-            let module = unsafe { Module::load(this.clone(), bytecode)? };
-            let (module, promise) = module.eval()?;
-
-            handle_maybe_promise(this.clone(), promise.into())?;
-
-            if let Some(fn_name) = fn_name {
-                let fun: Function = module.get(fn_name)?;
-                // Exported functions are guaranteed not to have arguments so
-                // we can safely pass an empty tuple for arguments.
-                let value = fun.call(())?;
-                handle_maybe_promise(this.clone(), value)?
-            }
-            Ok(())
-        })
+        .with(f)
         .map_err(|e| runtime.context().with(|cx| from_js_error(cx.clone(), e)))
-        .and_then(|_: ()| ensure_pending_jobs(runtime))
+        .and_then(|_: ()| ensure_pending_jobs(runtime));
+
+    // `Javy.exit` unwinds the stack by throwing an uncatchable error, which
+    // must not be reported as a failure.
+    if runtime.exit_requested() {
+        exit(runtime.exit_code().unwrap_or_default());
+    }
+    result?;
+
+    match runtime.exit_code() {
+        Some(code) if code != 0 => exit(code),
+        _ => Ok(()),
+    }
+}
+
+/// Calls the function exported with `fn_name` from a module namespace.
+fn call_function<'js>(
+    this: Ctx<'js>,
+    namespace: Object<'js>,
+    fn_name: &str,
+) -> quickjs::Result<()> {
+    let fun: Function = namespace.get(fn_name)?;
+    // Exported functions are guaranteed not to have arguments so we can
+    // safely pass an empty tuple for arguments.
+    let value = fun.call(())?;
+    handle_maybe_promise(this, value)
+}
+
+/// Terminates the instance with the given exit code.
+///
+/// On WASI preview 1 this is translated to a call to `proc_exit`.
+fn exit(code: i32) -> ! {
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+    process::exit(code)
 }
 
 /// Handles the promise returned by evaluating the JS bytecode.
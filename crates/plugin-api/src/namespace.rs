@@ -1,5 +1,8 @@
 /// Create a custom section named `import_namespace` with the contents of the
 /// string argument.
+///
+/// Also creates a custom section named `javy_bytecode_version` declaring the
+/// version of the bytecode format evaluated by the plugin.
 #[macro_export]
 macro_rules! import_namespace {
     ($str:literal) => {
@@ -15,5 +18,8 @@ macro_rules! import_namespace {
             }
             arr
         };
+
+        #[unsafe(link_section = "javy_bytecode_version")]
+        pub static JAVY_BYTECODE_VERSION: [u8; 4] = $crate::javy::BYTECODE_VERSION.to_le_bytes();
     };
 }
@@ -0,0 +1,209 @@
+//! APIs for declaring and receiving the runtime config passed with `-J` by the
+//! Javy CLI.
+//!
+//! Plugins declare the properties they support with a struct deriving
+//! [`RuntimeConfig`] and export its schema with [`crate::config_schema`]. The
+//! CLI uses the schema to validate the properties, serializes the provided
+//! ones as a JSON object and writes it to stdin while the plugin is being
+//! initialized, both for `javy init-plugin` and for statically linked
+//! modules. No config is written when no properties are provided.
+//!
+//! ```ignore
+//! use javy_plugin_api::runtime_config::{RuntimeConfig, read_runtime_config};
+//! use javy_plugin_api::{Config, config_schema};
+//! use serde::Deserialize;
+//!
+//! #[derive(Default, Deserialize, RuntimeConfig)]
+//! #[serde(deny_unknown_fields, rename_all = "kebab-case")]
+//! struct MyConfig {
+//!     /// Whether to enable the event loop.
+//!     #[config(default = false)]
+//!     event_loop: Option<bool>,
+//!     /// The level of messages to log.
+//!     #[config(default = "info", values("debug", "info", "error"))]
+//!     log_level: Option<String>,
+//! }
+//!
+//! config_schema!(MyConfig);
+//!
+//! fn config() -> Config {
+//!     let my_config = read_runtime_config::<MyConfig>().unwrap().with_defaults();
+//!     let mut config = Config::default();
+//!     config.event_loop(my_config.event_loop.unwrap());
+//!     config
+//! }
+//! ```
+
+use std::cell::OnceCell;
+use std::io::{self, Read};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+pub use javy_plugin_api_macros::RuntimeConfig;
+pub use serde_json::Value;
+
+thread_local! {
+    static CONFIG_SCHEMA_BYTES: OnceCell<Vec<u8>> = const { OnceCell::new() };
+    static CONFIG_SCHEMA_RET_AREA: OnceCell<[u32; 2]> = const { OnceCell::new() };
+}
+
+/// A struct declaring the runtime config properties supported by a plugin.
+///
+/// Should be implemented with `#[derive(RuntimeConfig)]`. Every field is an
+/// `Option` named after the property in snake case, so the struct should be
+/// deserialized with `#[serde(rename_all = "kebab-case")]`. A field accepts
+/// `#[config(default = <expr>)]` to set the value used when the property isn't
+/// provided and `#[config(values(<expr>, ...))]` to restrict the values it
+/// accepts.
+pub trait RuntimeConfig: DeserializeOwned + Default {
+    /// Sets every property that wasn't provided to its default value.
+    fn with_defaults(self) -> Self;
+
+    /// The schema of the supported properties.
+    fn config_schema() -> ConfigSchema;
+}
+
+/// The runtime config properties supported by a plugin, as returned by the
+/// `config-schema` export.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSchema {
+    supported_properties: Vec<ConfigProperty>,
+}
+
+impl ConfigSchema {
+    /// Creates a schema with the supported properties.
+    pub fn new(supported_properties: Vec<ConfigProperty>) -> Self {
+        Self {
+            supported_properties,
+        }
+    }
+}
+
+/// A runtime config property.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigProperty {
+    name: String,
+    doc: String,
+    #[serde(rename = "type")]
+    ty: &'static str,
+    default: Option<Value>,
+    allowed_values: Option<Vec<Value>>,
+}
+
+impl ConfigProperty {
+    /// Creates a property of type `T` without a default value.
+    pub fn new<T: PropertyType>(name: impl Into<String>, doc: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            doc: doc.into(),
+            ty: T::NAME,
+            default: None,
+            allowed_values: None,
+        }
+    }
+
+    /// The value used when the property isn't provided.
+    pub fn default(&mut self, value: impl Into<Value>) -> &mut Self {
+        self.default = Some(value.into());
+        self
+    }
+
+    /// The only values the property accepts.
+    pub fn allowed_values(&mut self, values: impl IntoIterator<Item = Value>) -> &mut Self {
+        self.allowed_values = Some(values.into_iter().collect());
+        self
+    }
+}
+
+/// The type of a property as reported in the config schema.
+pub trait PropertyType {
+    /// The name of the type.
+    const NAME: &'static str;
+}
+
+impl PropertyType for bool {
+    const NAME: &'static str = "bool";
+}
+
+impl PropertyType for usize {
+    const NAME: &'static str = "integer";
+}
+
+impl PropertyType for String {
+    const NAME: &'static str = "string";
+}
+
+/// Serializes the schema of `T` and returns a pointer to its offset and
+/// length, as two 32-bit integers.
+///
+/// Used by [`crate::config_schema`] to implement the `config-schema` export.
+#[doc(hidden)]
+pub fn config_schema_ret_area<T: RuntimeConfig>() -> *const u32 {
+    CONFIG_SCHEMA_BYTES.with(|bytes| {
+        let bytes = bytes.get_or_init(|| serde_json::to_vec(&T::config_schema()).unwrap());
+        CONFIG_SCHEMA_RET_AREA.with(|ret_area| {
+            ret_area
+                .get_or_init(|| [bytes.as_ptr() as u32, bytes.len() as u32])
+                .as_ptr()
+        })
+    })
+}
+
+/// Creates a `config-schema` export returning the schema of the runtime config
+/// struct, so the Javy CLI can validate and list the properties provided with
+/// `-J`.
+///
+/// Only supported by WASI preview 1 plugins.
+///
+/// # Examples
+///
+/// ```ignore
+/// javy_plugin_api::config_schema!(MyConfig);
+/// ```
+#[macro_export]
+macro_rules! config_schema {
+    ($config:ty) => {
+        #[unsafe(export_name = "config-schema")]
+        fn config_schema() -> *const u32 {
+            $crate::runtime_config::config_schema_ret_area::<$config>()
+        }
+    };
+}
+
+/// Reads the runtime config from stdin.
+///
+/// Returns `T::default()` if no config was provided. Should be called from the
+/// function passed to [`crate::initialize_runtime`] or [`crate::javy_plugin`]
+/// to build the [`crate::Config`].
+///
+/// Stdin is used instead of an environment variable because the value set for
+/// an environment variable persists in the initialized plugin, so a different
+/// value couldn't be used to initialize the runtime with a different
+/// configuration.
+pub fn read_runtime_config<T>() -> Result<T>
+where
+    T: DeserializeOwned + Default,
+{
+    let mut config_bytes = vec![];
+    io::stdin()
+        .read_to_end(&mut config_bytes)
+        .context("Error reading runtime config from stdin")?;
+    parse_runtime_config(&config_bytes)
+}
+
+/// Parses the runtime config from its JSON representation.
+///
+/// Returns `T::default()` if `config` is empty.
+pub fn parse_runtime_config<T>(config: &[u8]) -> Result<T>
+where
+    T: DeserializeOwned + Default,
+{
+    if config.is_empty() {
+        return Ok(T::default());
+    }
+    serde_json::from_slice(config).context("Invalid runtime config")
+}
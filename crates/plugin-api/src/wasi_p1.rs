@@ -4,8 +4,10 @@ use std::{
     process, ptr, slice,
 };
 
+#[cfg(not(feature = "bytecode-only"))]
 static mut COMPILE_SRC_RET_AREA: [u32; 3] = [0; 3];
 
+#[cfg(not(feature = "bytecode-only"))]
 thread_local! {
     static BYTECODE: OnceCell<Vec<u8>> = const { OnceCell::new() };
 }
@@ -59,6 +61,7 @@ unsafe extern "C" fn cabi_realloc(
     new_mem as _
 }
 
+#[cfg(not(feature = "bytecode-only"))]
 #[unsafe(export_name = "compile-src")]
 unsafe extern "C" fn compile_src(src_ptr: *const u8, src_len: usize) -> *const u32 {
     let src = unsafe { slice::from_raw_parts(src_ptr, src_len) };
@@ -85,15 +88,44 @@ extern "C" fn invoke(
     fn_name_len: usize,
 ) {
     let bytecode = unsafe { slice::from_raw_parts(bytecode_ptr, bytecode_len) };
-    let mut fn_name = None;
-    if fn_name_discriminator != 0 {
-        let fn_name_string =
-            String::from_utf8_lossy(unsafe { slice::from_raw_parts(fn_name_ptr, fn_name_len) })
-                .into_owned();
-        fn_name = Some(fn_name_string);
-    }
+    let fn_name = fn_name(fn_name_discriminator, fn_name_ptr, fn_name_len);
     crate::invoke(bytecode, fn_name.as_deref()).unwrap_or_else(|e| {
         eprintln!("{e}");
         process::abort();
     });
 }
+
+#[cfg(feature = "compressed-bytecode")]
+#[unsafe(export_name = "invoke-compressed")]
+extern "C" fn invoke_compressed(
+    bytecode_ptr: *const u8,
+    bytecode_len: usize,
+    fn_name_discriminator: u32,
+    fn_name_ptr: *const u8,
+    fn_name_len: usize,
+) {
+    let bytecode = unsafe { slice::from_raw_parts(bytecode_ptr, bytecode_len) };
+    let fn_name = fn_name(fn_name_discriminator, fn_name_ptr, fn_name_len);
+    crate::invoke_compressed(bytecode, fn_name.as_deref()).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::abort();
+    });
+}
+
+/// Reads the optional function name passed to `invoke`.
+fn fn_name(discriminator: u32, ptr: *const u8, len: usize) -> Option<String> {
+    if discriminator == 0 {
+        return None;
+    }
+    let fn_name = unsafe { slice::from_raw_parts(ptr, len) };
+    Some(String::from_utf8_lossy(fn_name).into_owned())
+}
+
+#[unsafe(export_name = "call-export")]
+extern "C" fn call_export(fn_name_ptr: *const u8, fn_name_len: usize) {
+    let fn_name = unsafe { slice::from_raw_parts(fn_name_ptr, fn_name_len) };
+    crate::call_export(&String::from_utf8_lossy(fn_name)).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::abort();
+    });
+}
@@ -1,39 +1,108 @@
 use anyhow::{Result, bail};
+pub use javy_optimization::Optimization;
 use std::{borrow::Cow, fs};
 use walrus::{FunctionId, ImportKind, ValType};
+use wasm_opt::{OptimizationOptions, Pass};
 use wasmparser::{Parser, Payload};
 use wasmtime::{Engine, Linker, Store};
 use wasmtime_wasi::WasiCtxBuilder;
 use wasmtime_wizer::Wizer;
 
-/// Extract core module if it's a component, then run wasm-opt and Wizer to
-/// initialize a plugin.
-pub async fn initialize_plugin(wasm_bytes: &[u8]) -> Result<Vec<u8>> {
-    initialize_plugin_helper(wasm_bytes, false).await
+/// Extract core module if it's a component, then run wasm-opt with the given
+/// optimizations and Wizer to initialize a plugin.
+///
+/// The plugin's function names are only kept if `debug_info` is `true`, so
+/// they can be included in modules generated with debug info.
+///
+/// Calls to the function imports in `asyncify_imports`, given as
+/// `module.name`, are transformed with Binaryen's asyncify before the plugin
+/// is optimized and initialized, so they can suspend and resume the plugin.
+/// This lets hosts without support for JavaScript Promise Integration (JSPI)
+/// implement them asynchronously by unwinding and rewinding the plugin's stack
+/// with the `asyncify_*` exports added by the transformation.
+pub async fn initialize_plugin(
+    wasm_bytes: &[u8],
+    optimization: &Optimization,
+    debug_info: bool,
+    asyncify_imports: &[String],
+) -> Result<Vec<u8>> {
+    initialize_plugin_helper(
+        wasm_bytes,
+        optimization,
+        debug_info,
+        asyncify_imports,
+        false,
+    )
+    .await
 }
 
-/// Extract core module if it's a component, then run wasm-opt and Wizer to
-/// initialize a plugin deterministically.
+/// Extract core module if it's a component, then run wasm-opt with the given
+/// optimizations and Wizer to initialize a plugin deterministically.
 ///
 /// Uses fixed clocks, deterministic RNG (via
 /// [`deterministic-wasi-ctx`](https://crates.io/crates/deterministic-wasi-ctx)),
 /// and single-threaded compilation during Wizer pre-initialization so that
-/// identical input always produces identical output.
+/// identical input always produces identical output. `debug_info` and
+/// `asyncify_imports` behave as for [`initialize_plugin`].
 ///
 /// **Security note:** This replaces both `secure_random` and
 /// `insecure_random` with a seeded PRNG. WASI random APIs must not be
 /// relied upon for cryptographic security when this is enabled.
-pub async fn initialize_plugin_with_determinism(wasm_bytes: &[u8]) -> Result<Vec<u8>> {
-    initialize_plugin_helper(wasm_bytes, true).await
+pub async fn initialize_plugin_with_determinism(
+    wasm_bytes: &[u8],
+    optimization: &Optimization,
+    debug_info: bool,
+    asyncify_imports: &[String],
+) -> Result<Vec<u8>> {
+    initialize_plugin_helper(wasm_bytes, optimization, debug_info, asyncify_imports, true).await
 }
 
-async fn initialize_plugin_helper(wasm_bytes: &[u8], determinism: bool) -> Result<Vec<u8>> {
+async fn initialize_plugin_helper(
+    wasm_bytes: &[u8],
+    optimization: &Optimization,
+    debug_info: bool,
+    asyncify_imports: &[String],
+    determinism: bool,
+) -> Result<Vec<u8>> {
     let wasm_bytes = extract_core_module_if_necessary(wasm_bytes)?;
-    let wasm_bytes = optimize_module(&wasm_bytes)?;
+    let wasm_bytes = if asyncify_imports.is_empty() {
+        wasm_bytes
+    } else {
+        asyncify(&wasm_bytes, asyncify_imports, debug_info)?.into()
+    };
+    let wasm_bytes = optimization.run(&wasm_bytes, debug_info)?;
     let wasm_bytes = preinitialize_module(&wasm_bytes, determinism).await?;
     Ok(wasm_bytes)
 }
 
+/// Runs Binaryen's asyncify transformation on calls to the given function
+/// imports.
+fn asyncify(wasm_bytes: &[u8], imports: &[String], debug_info: bool) -> Result<Vec<u8>> {
+    let module = walrus::Module::from_buffer(wasm_bytes)?;
+    for import in imports {
+        let imported = import.split_once('.').is_some_and(|(module_name, name)| {
+            module.imports.iter().any(|i| {
+                i.module == module_name
+                    && i.name == name
+                    && matches!(i.kind, ImportKind::Function(_))
+            })
+        });
+        if !imported {
+            bail!("Plugin doesn't import a function named `{import}` to asyncify");
+        }
+    }
+
+    let tempdir = tempfile::tempdir()?;
+    let tempfile_path = tempdir.path().join("temp.wasm");
+    fs::write(&tempfile_path, wasm_bytes)?;
+    OptimizationOptions::new_opt_level_0()
+        .add_pass(Pass::Asyncify)
+        .set_pass_arg("asyncify-imports", &imports.join(","))
+        .debug_info(debug_info)
+        .run(&tempfile_path, &tempfile_path)?;
+    Ok(fs::read(&tempfile_path)?)
+}
+
 /// Extracts core plugin module from a plugin component.
 pub fn extract_core_module_if_necessary(wasm_bytes: &[u8]) -> Result<Cow<'_, [u8]>> {
     let is_component = Parser::is_component(wasm_bytes);
@@ -130,19 +199,6 @@ fn strip_wasi_p2_imports(wasm_bytes: &[u8]) -> Result<Vec<u8>> {
     Ok(module.emit_wasm())
 }
 
-fn optimize_module(wasm_bytes: &[u8]) -> Result<Vec<u8>> {
-    let temp_dir = tempfile::tempdir()?;
-    let infile = temp_dir.path().join("infile.wasm");
-    fs::write(&infile, wasm_bytes)?;
-    let outfile = temp_dir.path().join("outfile.wasm");
-    wasm_opt::OptimizationOptions::new_opt_level_3() // Aggressively optimize for speed.
-        .shrink_level(wasm_opt::ShrinkLevel::Level0) // Don't optimize for size at the expense of performance.
-        .debug_info(false)
-        .run(&infile, &outfile)?;
-    let optimized_wasm_bytes = fs::read(outfile)?;
-    Ok(optimized_wasm_bytes)
-}
-
 async fn preinitialize_module(wasm_bytes: &[u8], deterministic: bool) -> Result<Vec<u8>> {
     let engine = Engine::default();
     let mut builder = WasiCtxBuilder::new();
@@ -2,6 +2,7 @@ use std::{fs, path::PathBuf};
 
 use anyhow::Result;
 use clap::Parser;
+use javy_plugin_processing::Optimization;
 
 #[derive(clap::Parser)]
 #[command(about = "Initialize a Javy plugin")]
@@ -17,6 +18,26 @@ struct Args {
         help = "Produce deterministic output by using fixed clocks and seeded PRNG. Security note: both secure_random and insecure_random become non-secure."
     )]
     deterministic: bool,
+
+    #[arg(
+        long,
+        default_value = "speed",
+        help = "wasm-opt optimizations to run: none, speed, size, aggressive-size, or a comma separated list of wasm-opt passes"
+    )]
+    opt: Optimization,
+
+    #[arg(
+        long,
+        help = "Keep the plugin's function names so they can be included in modules generated with debug info"
+    )]
+    debug_info: bool,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Comma separated list of function imports, as module.name, that can suspend the plugin, to transform with Binaryen's asyncify"
+    )]
+    asyncify_imports: Vec<String>,
 }
 
 #[tokio::main]
@@ -24,9 +45,21 @@ async fn main() -> Result<()> {
     let args = Args::parse();
     let wasm_bytes = fs::read(&args.input)?;
     let wasm_bytes = if args.deterministic {
-        javy_plugin_processing::initialize_plugin_with_determinism(&wasm_bytes).await?
+        javy_plugin_processing::initialize_plugin_with_determinism(
+            &wasm_bytes,
+            &args.opt,
+            args.debug_info,
+            &args.asyncify_imports,
+        )
+        .await?
     } else {
-        javy_plugin_processing::initialize_plugin(&wasm_bytes).await?
+        javy_plugin_processing::initialize_plugin(
+            &wasm_bytes,
+            &args.opt,
+            args.debug_info,
+            &args.asyncify_imports,
+        )
+        .await?
     };
     fs::write(&args.output, wasm_bytes)?;
     Ok(())
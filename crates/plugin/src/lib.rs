@@ -1,6 +1,7 @@
-use std::io::{self, Read};
+use std::{process, slice};
 
 use javy_plugin_api::javy::Runtime;
+use javy_plugin_api::runtime_config::{parse_runtime_config, read_runtime_config};
 use javy_plugin_api::{Config, import_namespace};
 
 use crate::shared_config::SharedConfig;
@@ -10,26 +11,11 @@ mod shared_config;
 import_namespace!("javy-default-plugin-v4");
 
 fn config() -> Config {
-    // Read shared config JSON in from stdin.
-    // Using stdin instead of an environment variable because the value set for
-    // an environment variable will persist as the value set for that environment
-    // variable in subsequent invocations so a different value can't be used to
-    // initialize a runtime with a different configuration.
     let mut config = Config::default();
-    config
-        .text_encoding(true)
-        .javy_stream_io(true)
-        .simd_json_builtins(true);
-
-    let mut config_bytes = vec![];
-    let shared_config = match io::stdin().read_to_end(&mut config_bytes) {
-        Ok(0) => None,
-        Ok(_) => Some(SharedConfig::parse_from_json(&config_bytes).unwrap()),
-        Err(e) => panic!("Error reading from stdin: {e}"),
-    };
-    if let Some(shared_config) = shared_config {
-        shared_config.apply_to_config(&mut config);
-    }
+    config.javy_exit(true);
+
+    let shared_config: SharedConfig = read_runtime_config().unwrap();
+    shared_config.apply_to_config(&mut config);
     config
 }
 
@@ -41,3 +27,20 @@ fn modify_runtime(runtime: Runtime) -> Runtime {
 fn initialize_runtime() {
     javy_plugin_api::initialize_runtime(config, modify_runtime).unwrap();
 }
+
+/// Applies the runtime config of a dynamically linked module on top of the
+/// config the plugin was initialized with.
+#[unsafe(export_name = "configure-module")]
+unsafe extern "C" fn configure_module(config_ptr: *const u8, config_len: usize) {
+    let config_bytes = unsafe { slice::from_raw_parts(config_ptr, config_len) };
+    parse_runtime_config::<SharedConfig>(config_bytes)
+        .and_then(|shared_config| {
+            javy_plugin_api::configure_module(|config| {
+                shared_config.apply_overrides_to_config(config)
+            })
+        })
+        .unwrap_or_else(|e| {
+            eprintln!("{e}");
+            process::abort();
+        });
+}
@@ -1,44 +1,98 @@
 //! APIs and data structures for receiving runtime configuration from the Javy CLI.
 
-use std::cell::OnceCell;
-
-use anyhow::Result;
-use javy_plugin_api::Config;
+use javy_plugin_api::runtime_config::RuntimeConfig;
+use javy_plugin_api::{Config, config_schema};
 use serde::Deserialize;
 
-mod runtime_config;
+#[derive(Debug, Default, Deserialize, RuntimeConfig)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SharedConfig {
+    /// Whether to enable the `Javy.readSync` and `Javy.writeSync` builtins.
+    #[config(default = true)]
+    javy_stream_io: Option<bool>,
+    /// Whether to override the `JSON.parse` and `JSON.stringify`
+    /// implementations with an alternative, more performant, SIMD based
+    /// implemetation.
+    #[config(default = true)]
+    simd_json_builtins: Option<bool>,
+    /// Whether to enable support for the `TextEncoder` and `TextDecoder`
+    /// APIs.
+    #[config(default = true)]
+    text_encoding: Option<bool>,
+    /// Whether to enable the event loop.
+    #[config(default = false)]
+    event_loop: Option<bool>,
+    /// The limit on the max amount of memory, in bytes, the runtime will
+    /// use.
+    memory_limit: Option<usize>,
+    /// The number of bytes of allocations after which garbage collection
+    /// is triggered.
+    gc_threshold: Option<usize>,
+    /// The limit on the max size of stack, in bytes, the runtime will use.
+    max_stack_size: Option<usize>,
+    /// Whether to enable the `Date` intrinsic.
+    #[config(default = true)]
+    date: Option<bool>,
+    /// Whether to enable `eval` and the `Function` constructor.
+    #[config(default = true)]
+    eval: Option<bool>,
+    /// Whether to enable the regular expression compiler.
+    #[config(default = true)]
+    regexp_compiler: Option<bool>,
+    /// Whether to enable the `RegExp` intrinsic.
+    #[config(default = true)]
+    regexp: Option<bool>,
+    /// Whether to enable the `JSON` intrinsic.
+    #[config(default = true)]
+    json: Option<bool>,
+    /// Whether to enable the `Proxy` intrinsic.
+    #[config(default = true)]
+    proxy: Option<bool>,
+    /// Whether to enable the `Map` and `Set` intrinsics.
+    #[config(default = true)]
+    map_set: Option<bool>,
+    /// Whether to enable the typed array intrinsics.
+    #[config(default = true)]
+    typed_array: Option<bool>,
+    /// Whether to enable the `Promise` intrinsic.
+    #[config(default = true)]
+    promise: Option<bool>,
+    /// Whether to enable the `WeakRef` intrinsic.
+    #[config(default = false)]
+    weak_ref: Option<bool>,
+    /// Whether to enable the `performance` intrinsic.
+    #[config(default = false)]
+    performance: Option<bool>,
+}
 
-use crate::runtime_config;
+config_schema!(SharedConfig);
 
-thread_local! {
-    static CONFIG_BYTES: OnceCell<Vec<u8>> = const { OnceCell::new() };
-    static CONFIG_RET_AREA: OnceCell<[u32; 2]> = const { OnceCell::new() };
-}
+impl SharedConfig {
+    /// Applies the properties to `config`, using the default value of every
+    /// property that wasn't provided.
+    pub fn apply_to_config(self, config: &mut Config) {
+        self.without_simd_json_builtins_if_json_disabled()
+            .with_defaults()
+            .apply_provided_to_config(config);
+    }
 
-runtime_config! {
-    #[derive(Debug, Default, Deserialize)]
-    #[serde(deny_unknown_fields, rename_all = "kebab-case")]
-    pub struct SharedConfig {
-        /// Whether to enable the `Javy.readSync` and `Javy.writeSync` builtins.
-        javy_stream_io: Option<bool>,
-        /// Whether to override the `JSON.parse` and `JSON.stringify`
-        /// implementations with an alternative, more performant, SIMD based
-        /// implemetation.
-        simd_json_builtins: Option<bool>,
-        /// Whether to enable support for the `TextEncoder` and `TextDecoder`
-        /// APIs.
-        text_encoding: Option<bool>,
-        /// Whether to enable the event loop.
-        event_loop: Option<bool>,
+    /// Applies only the provided properties to `config`, leaving the others
+    /// unchanged.
+    pub fn apply_overrides_to_config(self, config: &mut Config) {
+        self.without_simd_json_builtins_if_json_disabled()
+            .apply_provided_to_config(config);
     }
-}
 
-impl SharedConfig {
-    pub fn parse_from_json(config: &[u8]) -> Result<Self> {
-        Ok(serde_json::from_slice::<Self>(config)?)
+    /// The SIMD JSON builtins require the `JSON` intrinsic so they're disabled
+    /// when it's disabled, unless they were explicitly provided.
+    fn without_simd_json_builtins_if_json_disabled(mut self) -> Self {
+        if self.json == Some(false) && self.simd_json_builtins.is_none() {
+            self.simd_json_builtins = Some(false);
+        }
+        self
     }
 
-    pub fn apply_to_config(&self, config: &mut Config) {
+    fn apply_provided_to_config(self, config: &mut Config) {
         if let Some(enable) = self.javy_stream_io {
             config.javy_stream_io(enable);
         }
@@ -51,23 +105,47 @@ impl SharedConfig {
         if let Some(enable) = self.event_loop {
             config.event_loop(enable);
         }
+        if let Some(bytes) = self.memory_limit {
+            config.memory_limit(bytes);
+        }
+        if let Some(bytes) = self.gc_threshold {
+            config.gc_threshold(bytes);
+        }
+        if let Some(bytes) = self.max_stack_size {
+            config.max_stack_size(bytes);
+        }
+        if let Some(enable) = self.date {
+            config.date(enable);
+        }
+        if let Some(enable) = self.eval {
+            config.eval(enable);
+        }
+        if let Some(enable) = self.regexp_compiler {
+            config.regexp_compiler(enable);
+        }
+        if let Some(enable) = self.regexp {
+            config.regexp(enable);
+        }
+        if let Some(enable) = self.json {
+            config.json(enable);
+        }
+        if let Some(enable) = self.proxy {
+            config.proxy(enable);
+        }
+        if let Some(enable) = self.map_set {
+            config.map_set(enable);
+        }
+        if let Some(enable) = self.typed_array {
+            config.typed_array(enable);
+        }
+        if let Some(enable) = self.promise {
+            config.promise(enable);
+        }
+        if let Some(enable) = self.weak_ref {
+            config.weak_ref(enable);
+        }
+        if let Some(enable) = self.performance {
+            config.performance(enable);
+        }
     }
 }
-
-#[unsafe(export_name = "config-schema")]
-fn config_schema() -> *const u32 {
-    let bytes = serde_json::to_string(&SharedConfig::config_schema())
-        .unwrap()
-        .as_bytes()
-        .to_vec();
-    let bytes_len = bytes.len();
-    CONFIG_BYTES.with(|key| key.set(bytes)).unwrap();
-    CONFIG_RET_AREA.with(|key| {
-        key.set([
-            CONFIG_BYTES.with(|key| key.get().unwrap().as_ptr()) as u32,
-            bytes_len as u32,
-        ])
-        .unwrap();
-        key.get().unwrap().as_ptr()
-    })
-}
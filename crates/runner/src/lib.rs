@@ -88,6 +88,12 @@ pub struct Builder {
     text_encoding: Option<bool>,
     /// Whether to enable the event loop.
     event_loop: Option<bool>,
+    /// Whether to enable `eval` and the `Function` constructor.
+    eval: Option<bool>,
+    /// Whether to enable the `Proxy` intrinsic.
+    proxy: Option<bool>,
+    /// The memory limit of the JS runtime.
+    memory_limit: Option<String>,
     built: bool,
     /// Preload the module at path, using the given instance name.
     preload: Option<(String, PathBuf)>,
@@ -97,6 +103,14 @@ pub struct Builder {
     source_code: Option<Source>,
     /// Whether to enable deterministic builds.
     deterministic: Option<bool>,
+    /// Whether to evaluate the top-level scope at build time.
+    pre_evaluate: Option<bool>,
+    /// Whether to strip unused code from the module.
+    strip_unused: Option<bool>,
+    /// Whether to keep function names in the module.
+    debug_info: Option<bool>,
+    /// Whether to compress the bytecode in the module.
+    compress_bytecode: Option<bool>,
 }
 
 impl Default for Builder {
@@ -113,9 +127,16 @@ impl Default for Builder {
             simd_json_builtins: None,
             text_encoding: None,
             event_loop: None,
+            eval: None,
+            proxy: None,
+            memory_limit: None,
             plugin: Plugin::Default,
             source_code: None,
             deterministic: None,
+            pre_evaluate: None,
+            strip_unused: None,
+            debug_info: None,
+            compress_bytecode: None,
         }
     }
 }
@@ -171,6 +192,21 @@ impl Builder {
         self
     }
 
+    pub fn eval(&mut self, enabled: bool) -> &mut Self {
+        self.eval = Some(enabled);
+        self
+    }
+
+    pub fn proxy(&mut self, enabled: bool) -> &mut Self {
+        self.proxy = Some(enabled);
+        self
+    }
+
+    pub fn memory_limit(&mut self, limit: impl Into<String>) -> &mut Self {
+        self.memory_limit = Some(limit.into());
+        self
+    }
+
     pub fn plugin(&mut self, plugin: Plugin) -> &mut Self {
         self.plugin = plugin;
         self
@@ -186,6 +222,26 @@ impl Builder {
         self
     }
 
+    pub fn pre_evaluate(&mut self, enabled: bool) -> &mut Self {
+        self.pre_evaluate = Some(enabled);
+        self
+    }
+
+    pub fn strip_unused(&mut self, enabled: bool) -> &mut Self {
+        self.strip_unused = Some(enabled);
+        self
+    }
+
+    pub fn debug_info(&mut self, enabled: bool) -> &mut Self {
+        self.debug_info = Some(enabled);
+        self
+    }
+
+    pub fn compress_bytecode(&mut self, enabled: bool) -> &mut Self {
+        self.compress_bytecode = Some(enabled);
+        self
+    }
+
     pub fn build(&mut self) -> Result<Runner> {
         if self.built {
             bail!("Builder already used to build a runner")
@@ -207,11 +263,18 @@ impl Builder {
             simd_json_builtins,
             text_encoding,
             event_loop,
+            eval,
+            proxy,
+            memory_limit,
             built: _,
             preload,
             plugin,
             source_code,
             deterministic,
+            pre_evaluate,
+            strip_unused,
+            debug_info,
+            compress_bytecode,
         } = std::mem::take(self);
 
         self.built = true;
@@ -226,16 +289,25 @@ impl Builder {
             simd_json_builtins,
             text_encoding,
             event_loop,
+            eval,
+            proxy,
+            memory_limit,
             preload,
             plugin,
             source_code,
             deterministic,
+            pre_evaluate,
+            strip_unused,
+            debug_info,
+            compress_bytecode,
         )
     }
 }
 
 pub struct Runner {
     pub wasm: Vec<u8>,
+    /// What `javy build` wrote to stderr when building the module.
+    pub build_stderr: String,
     linker: Linker<StoreContext>,
     initial_fuel: u64,
     preload: Option<(String, Vec<u8>)>,
@@ -296,10 +368,17 @@ impl Runner {
         override_json_parse_and_stringify: Option<bool>,
         text_encoding: Option<bool>,
         event_loop: Option<bool>,
+        eval: Option<bool>,
+        proxy: Option<bool>,
+        memory_limit: Option<String>,
         preload: Option<(String, PathBuf)>,
         plugin: Plugin,
         source_code: Option<Source>,
         deterministic: Option<bool>,
+        pre_evaluate: Option<bool>,
+        strip_unused: Option<bool>,
+        debug_info: Option<bool>,
+        compress_bytecode: Option<bool>,
     ) -> Result<Self> {
         // This directory is unique and will automatically get deleted
         // when `tempdir` goes out of scope.
@@ -318,12 +397,19 @@ impl Runner {
             &override_json_parse_and_stringify,
             &text_encoding,
             &event_loop,
+            &eval,
+            &proxy,
+            &memory_limit,
             &plugin,
             &source_code,
             &deterministic,
+            &pre_evaluate,
+            &strip_unused,
+            &debug_info,
+            &compress_bytecode,
         );
 
-        Self::exec_command(bin, root, args)?;
+        let build_stderr = Self::exec_command(bin, root, args)?;
 
         let wasm = fs::read(&wasm_file)?;
 
@@ -339,6 +425,7 @@ impl Runner {
 
         Ok(Self {
             wasm,
+            build_stderr,
             linker,
             initial_fuel: u64::MAX,
             preload,
@@ -349,6 +436,7 @@ impl Runner {
         let engine = Self::setup_engine();
         Ok(Self {
             wasm,
+            build_stderr: String::new(),
             linker: Self::setup_linker(&engine)?,
             initial_fuel: u64::MAX,
             preload: None,
@@ -425,9 +513,16 @@ impl Runner {
         simd_json_builtins: &Option<bool>,
         text_encoding: &Option<bool>,
         event_loop: &Option<bool>,
+        eval: &Option<bool>,
+        proxy: &Option<bool>,
+        memory_limit: &Option<String>,
         plugin: &Plugin,
         source_code: &Option<Source>,
         deterministic: &Option<bool>,
+        pre_evaluate: &Option<bool>,
+        strip_unused: &Option<bool>,
+        debug_info: &Option<bool>,
+        compress_bytecode: &Option<bool>,
     ) -> Vec<String> {
         let mut args = vec![
             "build".to_string(),
@@ -474,6 +569,21 @@ impl Runner {
             args.push(format!("event-loop={}", if enabled { "y" } else { "n" }));
         }
 
+        if let Some(enabled) = *eval {
+            args.push("-J".to_string());
+            args.push(format!("eval={}", if enabled { "y" } else { "n" }));
+        }
+
+        if let Some(enabled) = *proxy {
+            args.push("-J".to_string());
+            args.push(format!("proxy={}", if enabled { "y" } else { "n" }));
+        }
+
+        if let Some(limit) = memory_limit {
+            args.push("-J".to_string());
+            args.push(format!("memory-limit={limit}"));
+        }
+
         if plugin.needs_plugin_arg() {
             args.push("-C".to_string());
             args.push(format!("plugin={}", plugin.path().to_str().unwrap()));
@@ -496,10 +606,34 @@ impl Runner {
             args.push(format!("deterministic={}", if enabled { "y" } else { "n" }));
         }
 
+        if let Some(enabled) = *pre_evaluate {
+            args.push("-C".to_string());
+            args.push(format!("pre-evaluate={}", if enabled { "y" } else { "n" }));
+        }
+
+        if let Some(enabled) = *strip_unused {
+            args.push("-C".to_string());
+            args.push(format!("strip-unused={}", if enabled { "y" } else { "n" }));
+        }
+
+        if let Some(enabled) = *debug_info {
+            args.push("-C".to_string());
+            args.push(format!("debug-info={}", if enabled { "y" } else { "n" }));
+        }
+
+        if let Some(enabled) = *compress_bytecode {
+            args.push("-C".to_string());
+            args.push(format!(
+                "compress-bytecode={}",
+                if enabled { "y" } else { "n" }
+            ));
+        }
+
         args
     }
 
-    fn exec_command(bin: String, root: PathBuf, args: Vec<String>) -> Result<()> {
+    /// Runs `javy` and returns what it wrote to stderr.
+    fn exec_command(bin: String, root: PathBuf, args: Vec<String>) -> Result<String> {
         let output = Command::new(bin).current_dir(root).args(args).output()?;
 
         io::stdout().write_all(&output.stdout)?;
@@ -513,7 +647,7 @@ impl Runner {
             );
         }
 
-        Ok(())
+        Ok(String::from_utf8(output.stderr)?)
     }
 
     fn setup_engine() -> Engine {
@@ -544,6 +678,15 @@ impl Runner {
     }
 
     pub fn exec_func(&mut self, func: &str, input: Vec<u8>) -> Result<(Vec<u8>, Vec<u8>, u64)> {
+        self.exec_funcs(&[func], input)
+    }
+
+    /// Calls each of `funcs` in order on a single instance.
+    pub fn exec_funcs(
+        &mut self,
+        funcs: &[&str],
+        input: Vec<u8>,
+    ) -> Result<(Vec<u8>, Vec<u8>, u64)> {
         let mut store = Self::setup_store(self.linker.engine(), input)?;
         let module = Module::from_binary(self.linker.engine(), &self.wasm)?;
 
@@ -560,9 +703,14 @@ impl Runner {
         // Allow unknown imports for statically linked `test-plugin`.
         self.linker.define_unknown_imports_as_traps(&module)?;
         let instance = self.linker.instantiate(store.as_context_mut(), &module)?;
-        let run = instance.get_typed_func::<(), ()>(store.as_context_mut(), func)?;
-
-        let res = run.call(store.as_context_mut(), ());
+        let funcs = funcs
+            .iter()
+            .map(|func| instance.get_typed_func::<(), ()>(store.as_context_mut(), func))
+            .collect::<wasmtime::Result<Vec<_>>>()?;
+
+        let res = funcs
+            .iter()
+            .try_for_each(|run| run.call(store.as_context_mut(), ()));
 
         self.extract_store_data(res, store)
     }
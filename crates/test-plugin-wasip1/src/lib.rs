@@ -1,17 +1,50 @@
+use anyhow::{Result, bail};
 use javy_plugin_api::{
     Config, import_namespace,
     javy::{Runtime, quickjs::prelude::Func},
+    javy_fn,
 };
+use serde::{Deserialize, Serialize};
 
 import_namespace!("test-plugin-wasip1");
 
 #[link(wasm_import_module = "some_host")]
 unsafe extern "C" {
     fn imported_function();
+    fn get_value() -> u32;
+}
+
+#[derive(Deserialize, Serialize)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[javy_fn]
+fn byte_length(bytes: &[u8]) -> usize {
+    bytes.len()
+}
+
+#[javy_fn]
+fn greet(name: &str) -> String {
+    format!("Hello, {name}!")
+}
+
+#[javy_fn]
+fn translate(point: Point, offset: i32) -> Result<Point> {
+    if offset < 0 {
+        bail!("Offset must not be negative");
+    }
+    Ok(Point {
+        x: point.x + offset,
+        y: point.y,
+    })
 }
 
 fn config() -> Config {
-    Config::default()
+    let mut config = Config::default();
+    config.event_loop(true);
+    config
 }
 
 fn modify_runtime(runtime: Runtime) -> Runtime {
@@ -25,6 +58,16 @@ fn modify_runtime(runtime: Runtime) -> Runtime {
                 }),
             )
             .unwrap();
+        ctx.globals()
+            .set("byteLength", js_byte_length(&ctx).unwrap())
+            .unwrap();
+        ctx.globals().set("greet", js_greet(&ctx).unwrap()).unwrap();
+        ctx.globals()
+            .set("translate", js_translate(&ctx).unwrap())
+            .unwrap();
+        ctx.globals()
+            .set("getValue", Func::from(|| unsafe { get_value() }))
+            .unwrap();
     });
     runtime
 }